@@ -1,54 +1,164 @@
-use std::iter::zip;
+use std::collections::BTreeMap;
+use std::fmt::Write;
 
-pub fn is_prefix(needle: &str, haystack: &str) -> bool {
-    if haystack.len() < needle.len() {
-        return false;
+use crate::helpers::aho_corasick::AhoCorasick;
+
+/// The words that stand for numbers, and what each one is worth.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    words: BTreeMap<String, u32>,
+    ignore_case: bool,
+}
+
+impl Vocabulary {
+    pub fn new() -> Self {
+        Vocabulary::default()
+    }
+
+    /// Adds a word, replacing what it was worth if it's already there.
+    pub fn word(mut self, word: &str, value: u32) -> Self {
+        assert!(!word.is_empty(), "a word can't be empty");
+        self.words.insert(word.to_string(), value);
+        self
+    }
+
+    pub fn words(self, words: &[(&str, u32)]) -> Self {
+        words.iter().fold(self, |vocabulary, &(word, value)| {
+            vocabulary.word(word, value)
+        })
+    }
+
+    /// Matches ASCII letters in any case, so "Nine" is 9 too.
+    #[allow(unused)]
+    pub fn ignoring_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    #[allow(unused)]
+    pub fn value(&self, word: &str) -> Option<u32> {
+        self.words.get(word).copied()
     }
-    zip(needle.chars(), haystack.chars()).all(|(n, h)| n == h)
 }
 
-fn combine_first_and_last(line: &str, prefixes: &Vec<&str>) -> u32 {
-    let mut first_digit = 0;
-    let mut last_digit = 0;
+/// A word found in a line, and where.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// the text as written in the line
+    pub text: &'a str,
+    pub value: u32,
+    pub start: usize,
+}
 
-    for start in 0..line.len() {
-        let pointer = &line[start..];
+/// Finds the first and last number in a line, written as any of the words
+/// of a `Vocabulary`.
+pub struct Calibrator {
+    matcher: AhoCorasick,
+    values: Vec<u32>,
+}
 
-        if let Some((i, _)) = prefixes
+impl Calibrator {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        // longest first, so where two words start at the same place (like
+        // "1" and "10") the longer one wins
+        let mut words: Vec<(&str, u32)> = vocabulary
+            .words
             .iter()
-            .enumerate()
-            .map(|(i, x)| (i, is_prefix(x, pointer)))
-            .find(|(_, x)| *x)
-        {
-            let digit = i % 9 + 1;
-
-            if first_digit == 0 {
-                first_digit = digit
-            }
-            last_digit = digit
+            .map(|(word, &value)| (word.as_str(), value))
+            .collect();
+        words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+
+        let (words, values): (Vec<&str>, Vec<u32>) = words.into_iter().unzip();
+        let matcher = if vocabulary.ignore_case {
+            AhoCorasick::ignoring_ascii_case(&words)
+        } else {
+            AhoCorasick::new(&words)
+        };
+        Calibrator { matcher, values }
+    }
+
+    /// Every word in the line, in the order they start, including words
+    /// that share letters like the "eight" and "two" of "eightwo".
+    pub fn tokens<'a>(&self, line: &'a str) -> Vec<Token<'a>> {
+        let mut tokens: Vec<Token> = self
+            .matcher
+            .find_overlapping(line)
+            .map(|m| Token {
+                text: &line[m.start..m.end],
+                value: self.values[m.pattern],
+                start: m.start,
+            })
+            .collect();
+        tokens.sort_by_key(|token| (token.start, std::cmp::Reverse(token.text.len())));
+        tokens
+    }
+
+    // the first and last values written next to each other, `None` if
+    // there are none in the line
+    fn combine_first_and_last(&self, line: &str) -> Option<u32> {
+        let first = self.values[self.matcher.first(line)?.pattern];
+        let last = self.values[self.matcher.last(line)?.pattern];
+        Some(first * 10u32.pow(last.checked_ilog10().unwrap_or(0) + 1) + last)
+    }
+
+    fn accumulate(&self, file: &str) -> u32 {
+        file.lines()
+            .filter_map(|line| self.combine_first_and_last(line))
+            .sum()
+    }
+
+    /// What was found in a line and where, like
+    /// `"two1nine" = 29: two@0 1@3 nine@4`.
+    pub fn explain(&self, line: &str) -> String {
+        let mut explanation = format!("{line:?} = ");
+        match self.combine_first_and_last(line) {
+            Some(value) => write!(explanation, "{value}:").unwrap(),
+            None => explanation.push_str("nothing:"),
+        }
+        for token in self.tokens(line) {
+            write!(explanation, " {}@{}", token.text, token.start).unwrap();
         }
+        explanation
     }
-    (first_digit * 10 + last_digit).try_into().unwrap()
 }
 
-const DIGITS_AS_WORDS: [&str; 9] = [
-    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+const DIGITS_AS_WORDS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
 ];
-const DIGITS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+const DIGITS: [(&str, u32); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+fn part_1_vocabulary() -> Vocabulary {
+    Vocabulary::new().words(&DIGITS)
+}
 
-fn accumulate(file: &str, prefixes: &Vec<&str>) -> u32 {
-    file.lines().fold(0, |total, line| {
-        total + combine_first_and_last(line, &prefixes)
-    })
+fn part_2_vocabulary() -> Vocabulary {
+    part_1_vocabulary().words(&DIGITS_AS_WORDS)
 }
 
 pub fn solve_part_1(file: &str) -> Option<u32> {
-    Some(accumulate(file, &DIGITS.into()))
+    Some(Calibrator::new(&part_1_vocabulary()).accumulate(file))
 }
 
 pub fn solve_part_2(file: &str) -> Option<u32> {
-    let prefixes = [&DIGITS[..], &DIGITS_AS_WORDS[..]].concat();
-    Some(accumulate(file, &prefixes))
+    Some(Calibrator::new(&part_2_vocabulary()).accumulate(file))
 }
 
 #[allow(unused)]
@@ -58,16 +168,69 @@ pub fn main(file: &str) {
     println!("  part 2: {:?}", solve_part_2(&file));
 }
 
+/// Prints what was found on every line, to see why a calibration value
+/// isn't what was expected.
+pub fn diagnose(file: &str, vocabulary: &Vocabulary) {
+    let calibrator = Calibrator::new(vocabulary);
+    for (k, line) in file.lines().enumerate() {
+        println!("{:>5}: {}", k + 1, calibrator.explain(line));
+    }
+}
+
+/// `diagnose` with the part 2 words.
+pub fn report(file: &str) {
+    diagnose(file, &part_2_vocabulary());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::inputs::{self, InputType};
 
     #[test]
-    fn prefix_function_works() {
-        assert!(self::is_prefix("Hello", "Hello, World!"));
-        assert!(!self::is_prefix("a", "b"));
-        assert!(!self::is_prefix("bigger than", "bigger"));
+    fn uses_any_words() {
+        let vocabulary = Vocabulary::new()
+            .words(&[("zéro", 0), ("un", 1), ("deux", 2)])
+            .word("dix", 10);
+        let calibrator = Calibrator::new(&vocabulary);
+        assert_eq!(calibrator.combine_first_and_last("ünunxdeuxé"), Some(12));
+        assert_eq!(calibrator.combine_first_and_last("dixzéro"), Some(100));
+        assert_eq!(calibrator.combine_first_and_last("zérodix"), Some(10));
+        assert_eq!(calibrator.combine_first_and_last("über"), None);
+
+        // the longer word wins where two start together, but the "2" still
+        // starts last
+        let calibrator = Calibrator::new(&part_1_vocabulary().word("12", 12));
+        assert_eq!(calibrator.combine_first_and_last("x12"), Some(122));
+    }
+
+    #[test]
+    fn reports_overlapping_words() {
+        let calibrator = Calibrator::new(&part_2_vocabulary());
+        let tokens = calibrator.tokens("eightwo3");
+        let found: Vec<(&str, u32, usize)> = tokens
+            .iter()
+            .map(|token| (token.text, token.value, token.start))
+            .collect();
+        assert_eq!(found, vec![("eight", 8, 0), ("two", 2, 4), ("3", 3, 7)]);
+        assert_eq!(
+            calibrator.explain("eightwo"),
+            "\"eightwo\" = 82: eight@0 two@4"
+        );
+        assert_eq!(calibrator.explain("abc"), "\"abc\" = nothing:");
+    }
+
+    #[test]
+    fn ignores_case() {
+        let vocabulary = part_2_vocabulary();
+        assert_eq!(vocabulary.value("seven"), Some(7));
+        assert_eq!(
+            Calibrator::new(&vocabulary).combine_first_and_last("SEVENx2"),
+            Some(22)
+        );
+        let calibrator = Calibrator::new(&vocabulary.ignoring_case());
+        assert_eq!(calibrator.combine_first_and_last("SEVENx2"), Some(72));
+        assert_eq!(calibrator.tokens("NiNe")[0].text, "NiNe");
     }
 
     #[test]
@@ -1,48 +1,160 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::{cmp::max, iter::zip};
+use std::collections::{BTreeMap, BTreeSet};
 
-const GAME_ID_CAPTURE: &str = r"^Game (?P<id>\d+)";
-const NUM_COLOR_CAPTURE: &str = r"(?P<num>\d+) (?P<color>\w*)";
+use crate::helpers::parse::{parse_lines, Input, ParseError};
 
-fn get_game_id(line: &str) -> u32 {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(GAME_ID_CAPTURE).unwrap());
-    let caps = RE.captures(line).unwrap();
-    caps["id"].parse().unwrap()
+// how many cubes there are of each colour, in one draw or in a whole bag
+type Cubes<'a> = BTreeMap<&'a str, u32>;
+type Draw<'a> = Cubes<'a>;
+type Bag<'a> = Cubes<'a>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Game<'a> {
+    id: u32,
+    draws: Vec<Draw<'a>>,
 }
 
-fn smallest_hands(hand: &str) -> [u32; 3] {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(NUM_COLOR_CAPTURE).unwrap());
-    RE.captures_iter(hand)
-        .map(|c| c.extract())
-        .fold([0, 0, 0], |z, (_, [num, color])| {
-            let [r, g, b] = z;
-            let num: u32 = num.parse().unwrap();
-            match color {
-                "red" => [max(r, num), g, b],
-                "green" => [r, max(g, num), b],
-                "blue" => [r, g, max(b, num)],
-                _ => panic!(),
+impl<'a> Game<'a> {
+    // Game 1: 3 blue, 4 red; 1 red, 2 green
+    fn parse(input: &mut Input<'a>) -> Result<Self, ParseError> {
+        input.literal("Game ")?;
+        let id = input.number()?;
+        input.literal(": ")?;
+        let draws = input.separated("; ", |input| {
+            let cubes = input.separated(", ", |input| {
+                let num: u32 = input.number()?;
+                input.literal(" ")?;
+                Ok((input.ident()?, num))
+            })?;
+            let mut draw = Draw::new();
+            for (color, num) in cubes {
+                *draw.entry(color).or_default() += num;
             }
-        })
+            Ok(draw)
+        })?;
+        Ok(Game { id, draws })
+    }
+
+    /// the fewest cubes of each colour the game could have been played with
+    fn minimum_bag(&self) -> Bag<'a> {
+        let mut bag = Bag::new();
+        for (&color, &num) in self.draws.iter().flatten() {
+            let most = bag.entry(color).or_default();
+            *most = (*most).max(num);
+        }
+        bag
+    }
+
+    fn possible_with(&self, bag: &Bag) -> bool {
+        self.minimum_bag()
+            .iter()
+            .all(|(color, &num)| bag.get(color).copied().unwrap_or(0) >= num)
+    }
 }
 
-pub fn solve_part_1(file: &str) -> Option<u32> {
-    const MAX_HANDS: [u32; 3] = [12, 13, 14];
-    Some(file.lines().fold(0, |z, line: &str| {
-        let hand = smallest_hands(&line);
-        if zip(hand, MAX_HANDS).all(|(h, m)| h <= m) {
-            z + get_game_id(line)
-        } else {
-            z
+fn parse(file: &str) -> Vec<Game<'_>> {
+    parse_lines(file, Game::parse).unwrap()
+}
+
+// the product of how many there are of each of `colors`, so a colour the bag
+// doesn't have makes it 0
+fn power(bag: &Bag, colors: &[&str]) -> u32 {
+    colors
+        .iter()
+        .map(|color| bag.get(color).copied().unwrap_or(0))
+        .product()
+}
+
+fn possible_games<'a, 'b>(
+    games: &'b [Game<'a>],
+    bag: &'b Bag,
+) -> impl Iterator<Item = &'b Game<'a>> {
+    games.iter().filter(move |game| game.possible_with(bag))
+}
+
+/// The bag with the fewest cubes in total that makes at least `n` of the
+/// games possible, `None` if there aren't that many games.
+///
+/// Tries every amount some game needs for each colour in turn, so it gets
+/// slow with lots of colours.
+#[allow(unused)]
+fn smallest_bag_for<'a>(games: &[Game<'a>], n: usize) -> Option<Bag<'a>> {
+    if n > games.len() {
+        return None;
+    }
+    let needed: Vec<Bag> = games.iter().map(Game::minimum_bag).collect();
+    let colors: Vec<&str> = needed
+        .iter()
+        .flat_map(|bag| bag.keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut best = None;
+    fill_bag(needed.iter().collect(), &colors, n, Bag::new(), &mut best);
+    best.map(|(_, bag)| bag)
+}
+
+// picks how many of the first colour go in the bag, out of the amounts the
+// games that still fit need, then moves on to the next colour
+fn fill_bag<'a>(
+    fitting: Vec<&Bag<'a>>,
+    colors: &[&'a str],
+    n: usize,
+    bag: Bag<'a>,
+    best: &mut Option<(u32, Bag<'a>)>,
+) {
+    let total: u32 = bag.values().sum();
+    let Some((&color, colors)) = colors.split_first() else {
+        if best
+            .as_ref()
+            .is_none_or(|(best_total, _)| total < *best_total)
+        {
+            *best = Some((total, bag));
         }
-    }))
+        return;
+    };
+
+    let need = |game: &Bag| game.get(color).copied().unwrap_or(0);
+    let amounts: BTreeSet<u32> = fitting.iter().map(|game| need(game)).collect();
+    for num in amounts {
+        if best
+            .as_ref()
+            .is_some_and(|(best_total, _)| total + num >= *best_total)
+        {
+            // the amounts only go up from here
+            break;
+        }
+        let still_fitting: Vec<&Bag> = fitting
+            .iter()
+            .copied()
+            .filter(|game| need(game) <= num)
+            .collect();
+        if still_fitting.len() < n {
+            continue;
+        }
+        let mut bag = bag.clone();
+        if num > 0 {
+            bag.insert(color, num);
+        }
+        fill_bag(still_fitting, colors, n, bag, best);
+    }
+}
+
+pub fn solve_part_1(file: &str) -> Option<u32> {
+    let bag = Bag::from([("red", 12), ("green", 13), ("blue", 14)]);
+    let games = parse(file);
+    Some(possible_games(&games, &bag).map(|game| game.id).sum())
 }
 
 pub fn solve_part_2(file: &str) -> Option<u32> {
-    Some(file.lines().fold(0, |z, line| {
-        z + smallest_hands(&line).iter().fold(1, |z, u| z * u)
-    }))
+    let games = parse(file);
+    let colors = ["red", "green", "blue"];
+    Some(
+        games
+            .iter()
+            .map(|game| power(&game.minimum_bag(), &colors))
+            .sum(),
+    )
 }
 
 #[allow(unused)]
@@ -60,11 +172,49 @@ mod tests {
     const DAY: u8 = 2;
 
     #[test]
-    fn regex_captures_game_id() {
+    fn parses_games() {
         let input = "Game 100: 8 red, 2 blue, 1 green; 2 blue, 4 red, 2 green; 9 red, 1 green; 2 green, 2 red; 3 red, 5 blue; 5 blue, 8 red";
-        let re = Regex::new(GAME_ID_CAPTURE).unwrap();
-        let caps = re.captures(input).unwrap();
-        assert_eq!(caps["id"].parse::<u32>().unwrap(), 100);
+        let game = parse(input).remove(0);
+        assert_eq!(game.id, 100);
+        assert_eq!(game.draws.len(), 6);
+        assert_eq!(
+            game.draws[0],
+            Draw::from([("red", 8), ("blue", 2), ("green", 1)])
+        );
+
+        let error = parse_lines("Game 1: 3 blue\nGame 2 4 red", Game::parse).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), 7));
+    }
+
+    #[test]
+    fn works_with_any_colors() {
+        let game = parse("Game 7: 2 teal, 1 red; 3 teal").remove(0);
+        assert_eq!(game.minimum_bag(), Bag::from([("teal", 3), ("red", 1)]));
+        assert!(!game.possible_with(&Bag::from([("red", 5), ("blue", 5)])));
+        assert!(game.possible_with(&Bag::from([("red", 1), ("teal", 3)])));
+    }
+
+    #[test]
+    fn powers_need_every_color() {
+        let games = parse("Game 1: 3 red, 4 blue; 2 red\nGame 2: 1 red, 2 green, 3 blue");
+        let colors = ["red", "green", "blue"];
+        assert_eq!(power(&games[0].minimum_bag(), &colors), 0);
+        assert_eq!(power(&games[1].minimum_bag(), &colors), 6);
+        assert_eq!(solve_part_2("Game 1: 3 red, 4 blue; 2 red"), Some(0));
+    }
+
+    #[test]
+    fn finds_the_smallest_bags() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let games = parse(&content);
+        let bag = |r, g, b| Bag::from([("red", r), ("green", g), ("blue", b)]);
+
+        assert_eq!(smallest_bag_for(&games, 1), Some(bag(1, 3, 4)));
+        assert_eq!(smallest_bag_for(&games, 3), Some(bag(6, 3, 6)));
+        let everything = smallest_bag_for(&games, 5).unwrap();
+        assert_eq!(everything, bag(20, 13, 15));
+        assert_eq!(possible_games(&games, &everything).count(), 5);
+        assert_eq!(smallest_bag_for(&games, 6), None);
     }
 
     #[test]
@@ -1,90 +1,131 @@
-use std::{char, cmp::min, usize};
+use std::collections::HashMap;
+use std::ops::Range;
 
-fn to_grid(file: &str) -> Vec<Vec<u8>> {
-    file.lines().map(|line| line.as_bytes().into()).collect()
+fn is_symbol(c: char) -> bool {
+    !matches!(c, '.' | '0'..='9')
 }
 
-fn is_symbol(c: u8) -> bool {
-    match c {
-        b'.' => false,
-        b'0'..=b'9' => false,
-        _ => true,
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Number {
+    row: usize,
+    /// the columns of its digits
+    cols: Range<usize>,
+    value: u32,
 }
 
-fn is_gear(c: u8) -> bool {
-    match c {
-        b'*' => true,
-        _ => false,
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol {
+    row: usize,
+    col: usize,
+    c: char,
 }
 
-// takes a pos with a number and finds the start and end to construct a number
-fn to_number(grid: &Vec<Vec<u8>>, pos: (usize, usize)) -> u32 {
-    let (x, y) = pos;
-    assert!(grid[y][x].is_ascii_digit());
-    let start_x = (0..=x)
-        .rev()
-        .take_while(|x| grid[y][*x].is_ascii_digit())
-        .last()
-        .unwrap();
-
-    grid[y][start_x..]
-        .iter()
-        .take_while(|x| x.is_ascii_digit())
-        .fold(0, |z, u| z * 10 + (*u as char).to_digit(10).unwrap())
+/// The numbers and symbols of an engine schematic, and which are next to
+/// which, diagonals included. Lines don't have to be the same length.
+#[derive(Debug, Clone)]
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    // for each number the symbols next to it, and the other way round
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
 }
 
-fn surrounding_numbers(grid: &Vec<Vec<u8>>, pos: (usize, usize)) -> Vec<u32> {
-    let (x, y) = pos;
-    let mut numbers = Vec::new();
-    for j in y.checked_sub(1).unwrap_or(0)..=min(y + 1, grid.len()) {
-        for i in x.checked_sub(1).unwrap_or(0)..=min(x + 1, grid.len()) {
-            if grid[j][i].is_ascii_digit() {
-                // do some duplicate checking
-                if i > 0 && i >= x && grid[j][i - 1].is_ascii_digit() {
-                    continue;
+impl Schematic {
+    fn parse(file: &str) -> Self {
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+        for (row, line) in file.lines().enumerate() {
+            let mut current: Option<Number> = None;
+            for (col, c) in line.chars().enumerate() {
+                match (c.to_digit(10), current.as_mut()) {
+                    (Some(digit), Some(number)) => {
+                        number.cols.end = col + 1;
+                        number.value = number.value * 10 + digit;
+                    }
+                    (Some(digit), None) => {
+                        current = Some(Number {
+                            row,
+                            cols: col..col + 1,
+                            value: digit,
+                        })
+                    }
+                    (None, _) => {
+                        numbers.extend(current.take());
+                        if is_symbol(c) {
+                            symbols.push(Symbol { row, col, c });
+                        }
+                    }
                 }
-                numbers.push(to_number(grid, (i, j)));
             }
+            numbers.extend(current);
         }
-    }
-    numbers
-}
 
-pub fn solve_part_1(file: &str) -> Option<u32> {
-    let mut total = 0;
-    let grid = to_grid(file);
-    // its a square
-    assert_eq!(grid.len(), grid[0].len());
-
-    for j in 0..grid.len() {
-        for i in 0..grid.len() {
-            if is_symbol(grid[j][i]) {
-                total += surrounding_numbers(&grid, (i, j)).iter().sum::<u32>();
+        let symbol_at: HashMap<(usize, usize), usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(k, symbol)| ((symbol.row, symbol.col), k))
+            .collect();
+        let mut number_symbols = vec![vec![]; numbers.len()];
+        let mut symbol_numbers = vec![vec![]; symbols.len()];
+        for (k, number) in numbers.iter().enumerate() {
+            for row in number.row.saturating_sub(1)..=number.row + 1 {
+                for col in number.cols.start.saturating_sub(1)..=number.cols.end {
+                    if let Some(&s) = symbol_at.get(&(row, col)) {
+                        number_symbols[k].push(s);
+                        symbol_numbers[s].push(k);
+                    }
+                }
             }
         }
+
+        Schematic {
+            numbers,
+            symbols,
+            number_symbols,
+            symbol_numbers,
+        }
+    }
+
+    /// the numbers next to at least one symbol that `f` picks, each once
+    fn numbers_next_to<'a, F>(&'a self, f: F) -> impl Iterator<Item = &'a Number> + 'a
+    where
+        F: Fn(char) -> bool + 'a,
+    {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(move |(_, symbols)| symbols.iter().any(|&s| f(self.symbols[s].c)))
+            .map(|(number, _)| number)
+    }
+
+    fn part_numbers(&self) -> impl Iterator<Item = &Number> + '_ {
+        self.numbers_next_to(|_| true)
     }
-    Some(total)
+
+    /// The numbers next to each `symbol` that has exactly `parts` of them.
+    fn gears(&self, symbol: char, parts: usize) -> impl Iterator<Item = Vec<&Number>> + '_ {
+        self.symbols
+            .iter()
+            .zip(&self.symbol_numbers)
+            .filter(move |(s, numbers)| s.c == symbol && numbers.len() == parts)
+            .map(|(_, numbers)| numbers.iter().map(|&n| &self.numbers[n]).collect())
+    }
+}
+
+pub fn solve_part_1(file: &str) -> Option<u32> {
+    let schematic = Schematic::parse(file);
+    Some(schematic.part_numbers().map(|number| number.value).sum())
 }
 
 pub fn solve_part_2(file: &str) -> Option<u32> {
-    let mut total = 0;
-    let grid = to_grid(file);
-    // its a square
-    assert_eq!(grid.len(), grid[0].len());
-
-    for j in 0..grid.len() {
-        for i in 0..grid.len() {
-            if is_gear(grid[j][i]) {
-                let numbs = surrounding_numbers(&grid, (i, j));
-                if numbs.len() == 2 {
-                    total = total + numbs.iter().product::<u32>()
-                }
-            }
-        }
-    }
-    Some(total)
+    let schematic = Schematic::parse(file);
+    Some(
+        schematic
+            .gears('*', 2)
+            .map(|numbers| numbers.iter().map(|number| number.value).product::<u32>())
+            .sum(),
+    )
 }
 
 const DAY: u8 = 3;
@@ -103,14 +144,45 @@ mod tests {
 
     #[test]
     fn detects_symbol() {
-        assert!(!is_symbol(b'.'));
-        assert!(!is_symbol(b'0'));
-        assert!(!is_symbol(b'5'));
-        assert!(!is_symbol(b'9'));
-        assert!(is_symbol(b'*'));
-        assert!(is_symbol(b'/'));
-        assert!(is_symbol(b'&'));
-        assert!(is_symbol(b'$'));
+        assert!(!is_symbol('.'));
+        assert!(!is_symbol('0'));
+        assert!(!is_symbol('5'));
+        assert!(!is_symbol('9'));
+        assert!(is_symbol('*'));
+        assert!(is_symbol('/'));
+        assert!(is_symbol('&'));
+        assert!(is_symbol('$'));
+    }
+
+    #[test]
+    fn links_numbers_and_symbols() {
+        // not square, and the last line is shorter
+        let schematic = Schematic::parse("467..\n...*.\n..35#\n1");
+        let values: Vec<u32> = schematic.numbers.iter().map(|n| n.value).collect();
+        assert_eq!(values, vec![467, 35, 1]);
+        assert_eq!(schematic.numbers[1].cols, 2..4);
+        assert_eq!(schematic.symbols.len(), 2);
+
+        // 35 is next to both symbols, but only counts once
+        let parts: Vec<u32> = schematic.part_numbers().map(|n| n.value).collect();
+        assert_eq!(parts, vec![467, 35]);
+        let next_to_hash: Vec<u32> = schematic
+            .numbers_next_to(|c| c == '#')
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(next_to_hash, vec![35]);
+
+        // `f` can borrow from the caller
+        let set = ['*', '#'];
+        let next_to_set: Vec<u32> = schematic
+            .numbers_next_to(|c| set.contains(&c))
+            .map(|n| n.value)
+            .collect();
+        assert_eq!(next_to_set, vec![467, 35]);
+
+        assert_eq!(schematic.gears('*', 2).count(), 1);
+        assert_eq!(schematic.gears('*', 1).count(), 0);
+        assert_eq!(schematic.gears('#', 1).next().unwrap()[0].value, 35);
     }
 
     #[test]
@@ -1,42 +1,93 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use crate::helpers::parse::{parse_lines, Input, ParseError};
 
-const REGEX: &str = r"(\d+( |$))|(\|)";
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Card {
+    id: u32,
+    winning: Vec<u32>,
+    numbers: Vec<u32>,
+}
 
-fn card_matching(line: &str) -> u32 {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-    let caps: Vec<u32> = RE
-        .find_iter(line)
-        .map(|m| m.as_str().trim_end().parse().unwrap_or(0))
-        .collect();
+// numbers with any amount of space around them, up to something else
+fn number_list(input: &mut Input) -> Vec<u32> {
+    let mut numbers = vec![];
+    input.spaces();
+    while let Ok(number) = input.number() {
+        numbers.push(number);
+        input.spaces();
+    }
+    numbers
+}
 
-    let separator = caps
-        .iter()
-        .position(|&x| x == 0)
-        .expect("0 element in array");
-    let (winning, numbers) = (&caps[..separator], &caps[separator + 1..]);
+impl Card {
+    // Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+    fn parse(input: &mut Input) -> Result<Self, ParseError> {
+        input.literal("Card")?;
+        input.spaces();
+        let id = input.number()?;
+        input.literal(":")?;
+        let winning = number_list(input);
+        input.literal("|")?;
+        let numbers = number_list(input);
+        Ok(Card {
+            id,
+            winning,
+            numbers,
+        })
+    }
 
-    numbers
-        .iter()
-        .fold(0, |z, x| z + if winning.contains(x) { 1 } else { 0 })
+    fn matching(&self) -> usize {
+        self.numbers
+            .iter()
+            .filter(|x| self.winning.contains(x))
+            .count()
+    }
+}
+
+fn parse(file: &str) -> Vec<Card> {
+    parse_lines(file, Card::parse).unwrap()
+}
+
+/// How many copies of a card there are in the end, and where they came from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Copies {
+    count: u32,
+    /// the id of each earlier card that won copies of this one, and how many
+    won_from: Vec<(u32, u32)>,
+}
+
+/// Every card wins one copy of each of the next cards, as many as it has
+/// matching numbers, for every copy of it. Wins past the last card are lost.
+fn cascade(cards: &[Card]) -> Vec<Copies> {
+    let mut copies = vec![
+        Copies {
+            count: 1,
+            won_from: vec![],
+        };
+        cards.len()
+    ];
+    for (i, card) in cards.iter().enumerate() {
+        let count = copies[i].count;
+        let last = (i + card.matching()).min(cards.len() - 1);
+        for won in &mut copies[i + 1..=last] {
+            won.count += count;
+            won.won_from.push((card.id, count));
+        }
+    }
+    copies
 }
 
 pub fn solve_part_1(file: &str) -> Option<u32> {
     Some(
-        file.lines()
-            .fold(0, |z, line| z + (2 as u32).pow(card_matching(line)) / 2),
+        parse(file)
+            .iter()
+            .map(|card| 2u32.pow(card.matching() as u32) / 2)
+            .sum(),
     )
 }
 
 pub fn solve_part_2(file: &str) -> Option<u32> {
-    let scores: Vec<u32> = file.lines().map(|line| card_matching(line)).collect();
-    let mut counts = vec![1; scores.len()];
-    for (i, score) in scores.iter().enumerate() {
-        for j in i + 1..i + 1 + *score as usize {
-            counts[j] = counts[j] + counts[i]
-        }
-    }
-    Some(counts.iter().sum())
+    let cards = parse(file);
+    Some(cascade(&cards).iter().map(|copies| copies.count).sum())
 }
 
 const DAY: u8 = 4;
@@ -53,6 +104,32 @@ mod tests {
     use super::*;
     use crate::inputs::{self, InputType};
 
+    #[test]
+    fn parses_cards() {
+        let card = parse("Card  12:  0 48 | 83  0  6").remove(0);
+        assert_eq!(card.id, 12);
+        assert_eq!(card.winning, vec![0, 48]);
+        assert_eq!(card.numbers, vec![83, 0, 6]);
+        assert_eq!(card.matching(), 1);
+
+        let error = parse_lines("Card 1: 1 2 3", Card::parse).unwrap_err();
+        assert_eq!(error.column, 14);
+    }
+
+    #[test]
+    fn keeps_track_of_copies() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let copies = cascade(&parse(&content));
+        let counts: Vec<u32> = copies.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(copies[3].won_from, vec![(1, 1), (2, 2), (3, 4)]);
+        assert!(copies[5].won_from.is_empty());
+
+        // the last card's wins have nowhere to go
+        let copies = cascade(&parse("Card 1: 1 2 | 1 2 3\nCard 2: 5 | 5"));
+        assert_eq!(copies[1].count, 2);
+    }
+
     #[test]
     fn solves_first_problem() {
         let content = inputs::get_file(DAY, InputType::Sample);
@@ -1,188 +1,118 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use crate::helpers::intervals::{IntervalSet, PiecewiseLinear};
+use crate::helpers::parse::{parse_all, sections, Input, ParseError, Section};
 
-use std::ops::Range;
+type Seeds = IntervalSet<u64>;
 
-const REGEX: &str = r"(\d+)";
-fn line_to_digits(line: &str) -> Vec<u64> {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-    RE.find_iter(line)
-        .map(|m| m.as_str().trim_end().parse().unwrap_or(0))
-        .collect()
-}
-
-enum RangeOverlap {
-    Full,
-    Partial,
-    None,
-}
-impl RangeOverlap {
-    fn overlap<T: std::cmp::PartialOrd>(r1: &Range<T>, r2: &Range<T>) -> Self {
-        if r1.start <= r2.start && r2.end <= r1.end {
-            RangeOverlap::Full
-        } else if r2.end <= r1.start || r1.end <= r2.start {
-            RangeOverlap::None
-        } else {
-            RangeOverlap::Partial
-        }
-    }
-}
+type Map = PiecewiseLinear<u64>;
 
-#[derive(Clone, Copy)]
-struct Mapping {
-    src: u64,
-    dest: u64,
-    dist: u64,
+/// One of the maps, like "seed-to-soil".
+#[derive(Debug, Clone)]
+struct Stage<'a> {
+    from: &'a str,
+    to: &'a str,
+    map: Map,
 }
-impl Mapping {
-    fn to_range(self) -> SeedRange {
-        self.src..self.src + self.dist
-    }
-    fn start_and_end(self) -> (u64, u64) {
-        (self.src, self.src + self.dist)
-    }
 
-    // maps a single range, not more than one
-    fn map_range(&self, range: &SeedRange) -> SeedRange {
-        let (start, end) = (range.start, range.end);
-        let (m_start, _) = self.start_and_end();
-        match RangeOverlap::overlap(&self.to_range(), range) {
-            RangeOverlap::Full => (start - m_start) + self.dest..(end - m_start) + self.dest,
-            RangeOverlap::None => start..end,
-            RangeOverlap::Partial => panic!(),
+impl<'a> Stage<'a> {
+    // seed-to-soil map:
+    // 50 98 2
+    fn parse(section: &Section<'a>) -> Result<Self, ParseError> {
+        let (header, ranges) = section.text.split_once('\n').unwrap_or((section.text, ""));
+        let (from, to) = parse_all(header, |input| {
+            let from = input.ident()?;
+            input.literal("-to-")?;
+            let to = input.ident()?;
+            input.literal(" map:")?;
+            Ok((from, to))
+        })
+        .map_err(|e| ParseError {
+            line: Some(section.first_line),
+            ..e
+        })?;
+
+        let ranges = Section {
+            first_line: section.first_line + 1,
+            text: ranges,
+        };
+        let mut map = Map::new();
+        for [dest, src, length] in ranges.parse_lines(|input| {
+            let numbers = input.separated(" ", |input| input.number::<u64>())?;
+            <[u64; 3]>::try_from(numbers).map_err(|_| input.error("three numbers"))
+        })? {
+            map.add_piece(src..src + length, dest);
         }
+        Ok(Stage { from, to, map })
     }
 }
 
-struct Map {
-    mappings: Vec<Mapping>,
+#[derive(Debug, Clone)]
+struct Almanac<'a> {
+    seeds: Vec<u64>,
+    stages: Vec<Stage<'a>>,
 }
 
-type SeedRange = Range<u64>;
-
-type Seeds = Vec<SeedRange>;
-
-impl Map {
-    fn new_maps(lines: &[Vec<u64>]) -> Vec<Self> {
-        let maps: Vec<Vec<Vec<u64>>> = lines
-            .split(|line| line.is_empty())
-            .map(|l| l.to_vec())
-            .collect();
-
-        let maps: Vec<Vec<Vec<u64>>> = maps
-            .iter()
-            .filter(|m| !m.is_empty())
-            .map(|m| m.to_vec())
-            .collect();
-
-        maps.iter()
-            .map(|map| Map {
-                mappings: map
-                    .iter()
-                    .map(|mapping| Mapping {
-                        src: mapping[1],
-                        dest: mapping[0],
-                        dist: mapping[2],
-                    })
-                    .collect(),
-            })
-            .collect()
+impl<'a> Almanac<'a> {
+    fn parse(file: &'a str) -> Result<Self, ParseError> {
+        let sections = sections(file);
+        let (first, rest) = sections
+            .split_first()
+            .ok_or_else(|| Input::new(file).error("the seeds"))?;
+        let seeds = parse_all(first.text, |input| {
+            input.literal("seeds: ")?;
+            input.separated(" ", |input| input.number())
+        })
+        .map_err(|e| ParseError {
+            line: Some(first.first_line),
+            ..e
+        })?;
+        let stages = rest.iter().map(Stage::parse).collect::<Result<_, _>>()?;
+        Ok(Almanac { seeds, stages })
     }
 
-    fn map_seeds_over_self(&self, seeds: SeedRange) -> SeedRange {
-        for mapping in &self.mappings {
-            match RangeOverlap::overlap(&mapping.to_range(), &seeds) {
-                RangeOverlap::Full => return mapping.map_range(&seeds),
-                RangeOverlap::None => continue,
-                RangeOverlap::Partial => panic!(),
+    /// All the maps from `from` to `to` made into one, following the names
+    /// from one map to the next. `None` if there's no way through.
+    fn pipeline(&self, from: &str, to: &str) -> Option<Map> {
+        let mut map = Map::new();
+        let mut at = from;
+        let mut visited = vec![from];
+        while at != to {
+            let stage = self.stages.iter().find(|stage| stage.from == at)?;
+            if visited.contains(&stage.to) {
+                // the names go round without reaching `to`
+                return None;
             }
+            visited.push(stage.to);
+            map = map.then(&stage.map);
+            at = stage.to;
         }
-        seeds
+        Some(map)
     }
 
-    fn shatter_map(mapping: &Mapping, range: &SeedRange) -> Seeds {
-        let (start, end) = (range.start, range.end);
-        let (m_start, m_end) = (mapping.src, mapping.src + mapping.dist);
-        if m_start <= start && end <= m_end {
-            return vec![start..end];
-        } else if m_end <= start {
-            return vec![start..end];
-        } else if end <= m_start {
-            return vec![start..end];
-        } else if start < m_start {
-            return [
-                vec![start..m_start],
-                Map::shatter_map(mapping, &(m_start..end)),
-            ]
-            .concat();
-        } else if m_end < end {
-            return [Map::shatter_map(mapping, &(start..m_end)), vec![m_end..end]].concat();
-        } else {
-            panic!()
-        }
-    }
-
-    fn shatter_and_map_over_self(&self, range: &SeedRange) -> Seeds {
-        let (start, end) = (range.start, range.end);
-
-        let shattered: Seeds = self.mappings.iter().fold(vec![start..end], |z, mapping| {
-            z.iter()
-                .flat_map(|r| Map::shatter_map(mapping, r))
-                .collect()
-        });
-
-        shattered
-            .iter()
-            .map(|range| self.map_seeds_over_self(range.clone()))
-            .collect()
+    fn seed_to_location(&self) -> Map {
+        self.pipeline("seed", "location")
+            .expect("no maps from seed to location")
     }
+}
 
-    fn transform_seeds(&self, ranges: &Seeds) -> Seeds {
-        ranges
-            .iter()
-            .flat_map(|range| self.shatter_and_map_over_self(range))
-            .collect()
-    }
+fn lowest_location(seeds: Seeds, almanac: &Almanac) -> u64 {
+    let locations = seeds.map_through(&almanac.seed_to_location());
+    locations.min().unwrap()
 }
 
 pub fn solve_part_1(file: &str) -> Option<u64> {
-    let lines: Vec<Vec<u64>> = file.lines().map(|line| line_to_digits(line)).collect();
-
-    let (seeds, lines) = (lines[0..1][0].clone(), &lines[3..]);
-    let maps = Map::new_maps(lines);
-
-    let seeds: Seeds = seeds.iter().map(|seed| (*seed..(*seed + 1))).collect();
-
-    let seeds = maps.iter().fold(seeds, |z, u| u.transform_seeds(&z));
-
-    Some(
-        seeds
-            .iter()
-            .min_by(|x, y| x.start.cmp(&y.start))
-            .unwrap()
-            .start,
-    )
+    let almanac = Almanac::parse(file).unwrap();
+    let seeds: Seeds = almanac.seeds.iter().map(|&seed| seed..seed + 1).collect();
+    Some(lowest_location(seeds, &almanac))
 }
 
 pub fn solve_part_2(file: &str) -> Option<u64> {
-    let lines: Vec<Vec<u64>> = file.lines().map(|line| line_to_digits(line)).collect();
-
-    let (seeds, lines) = (lines[0..1][0].clone(), &lines[3..]);
-    let seeds: Seeds = seeds
+    let almanac = Almanac::parse(file).unwrap();
+    let seeds: Seeds = almanac
+        .seeds
         .chunks_exact(2)
-        .map(|chunk| (chunk[0]..chunk[0] + chunk[1]))
+        .map(|chunk| chunk[0]..chunk[0] + chunk[1])
         .collect();
-    let maps = Map::new_maps(lines);
-
-    let seeds = maps.iter().fold(seeds, |z, u| u.transform_seeds(&z));
-
-    Some(
-        seeds
-            .iter()
-            .min_by(|x, y| x.start.cmp(&y.start))
-            .unwrap()
-            .start,
-    )
+    Some(lowest_location(seeds, &almanac))
 }
 
 const DAY: u8 = 5;
@@ -200,22 +130,57 @@ mod tests {
     use crate::inputs::{self, InputType};
 
     #[test]
-    fn shatter_map_test() {
-        let mapping = Mapping {
-            src: 3,
-            dest: 9,
-            dist: 2,
-        };
+    fn map_seeds_test() {
+        let mut map = Map::new();
+        map.add_piece(3..5, 9);
+
+        let map_range = |range| Seeds::from(range).map_through(&map);
+        assert_eq!(map_range(1..3), Seeds::from(1..3));
+        assert_eq!(map_range(1..4).ranges(), &[1..3, 9..10]);
+        assert_eq!(map_range(3..5), Seeds::from(9..11));
+        assert_eq!(map_range(4..6).ranges(), &[5..6, 10..11]);
+        assert_eq!(map_range(5..7), Seeds::from(5..7));
+        assert_eq!(map_range(2..6).ranges(), &[2..3, 5..6, 9..11]);
+    }
 
-        assert_eq!(Map::shatter_map(&mapping, &(1..3)), vec![1..3]);
-        assert_eq!(Map::shatter_map(&mapping, &(1..4)), vec![1..3, 3..4]);
-        assert_eq!(Map::shatter_map(&mapping, &(3..5)), vec![3..5]);
-        assert_eq!(Map::shatter_map(&mapping, &(4..6)), vec![4..5, 5..6]);
-        assert_eq!(Map::shatter_map(&mapping, &(5..7)), vec![5..7]);
+    #[test]
+    fn composes_named_stages() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let almanac = Almanac::parse(&content).unwrap();
+        assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
+        let names: Vec<(&str, &str)> = almanac.stages.iter().map(|s| (s.from, s.to)).collect();
+        assert_eq!(names[0], ("seed", "soil"));
+        assert_eq!(names.len(), 7);
+
+        // the example's walk through the maps for seed 79
+        let to_soil = almanac.pipeline("seed", "soil").unwrap();
+        assert_eq!(to_soil.apply(79), 81);
         assert_eq!(
-            Map::shatter_map(&mapping, &(2..6)),
-            vec![(2..3), (3..5), (5..6)]
+            almanac.pipeline("soil", "fertilizer").unwrap().apply(81),
+            81
         );
+        assert_eq!(almanac.pipeline("seed", "location").unwrap().apply(79), 82);
+        assert!(almanac.pipeline("location", "seed").is_none());
+
+        // names that go round in a circle never get anywhere else
+        let circle = "seeds: 1\n\na-to-b map:\n0 0 5\n\nb-to-a map:\n5 0 5";
+        let circular = Almanac::parse(circle).unwrap();
+        assert!(circular.pipeline("a", "c").is_none());
+        assert_eq!(circular.pipeline("a", "a").unwrap().apply(3), 3);
+        assert_eq!(circular.pipeline("b", "a").unwrap().apply(3), 8);
+
+        // and back again, from the lowest locations to their seeds
+        let seed_to_location = almanac.seed_to_location();
+        assert!(seed_to_location.preimage(&Seeds::from(35..36)).contains(13));
+        assert!(seed_to_location.preimage(&Seeds::from(46..47)).contains(82));
+    }
+
+    #[test]
+    fn reports_bad_maps() {
+        let error = Almanac::parse("seeds: 1 2\n\nseed-to-soil map:\n1 2\n").unwrap_err();
+        assert_eq!(error.line, Some(4));
+        let error = Almanac::parse("seeds: 1 2\n\nseed to soil map:\n1 2 3\n").unwrap_err();
+        assert_eq!(error.line, Some(3));
     }
 
     #[test]
@@ -1,57 +1,46 @@
 use std::iter::zip;
+use std::ops::RangeInclusive;
 
-use once_cell::sync::Lazy;
-use regex::Regex;
+use crate::helpers::constructor::line_to_digits;
+use crate::helpers::numeric::BigInt;
 
 type IntType = u64;
 
-const REGEX: &str = r"(\d+)";
-fn line_to_digits(line: &str) -> Vec<IntType> {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-    RE.find_iter(line)
-        .map(|m| m.as_str().trim_end().parse().unwrap_or(0))
-        .collect()
-}
+/// Every hold time that goes further than `distance` in a race of `time`,
+/// `None` if none do.
+///
+/// Holding for `h` goes `h * (time - h)`, which is further than `distance`
+/// when `(2h - time)^2 < time^2 - 4 * distance`. That's worked out with big
+/// integers, so it's exact even when `time^2` doesn't fit in a `u128`.
+fn winning_holds<T>(time: T, distance: T) -> Option<RangeInclusive<T>>
+where
+    T: Copy + Into<u128> + TryFrom<u128>,
+{
+    let (time, distance): (u128, u128) = (time.into(), distance.into());
+    let (big_time, big_distance) = (BigInt::from(time), BigInt::from(distance));
+    let discriminant = big_time.clone() * big_time - BigInt::from(4i64) * big_distance;
+    if discriminant <= BigInt::from(0i64) {
+        return None;
+    }
+
+    // the furthest 2h - time can be from 0, which is odd when time is
+    let mut spread = (discriminant - BigInt::from(1i64))
+        .isqrt()
+        .to_u128()
+        .unwrap();
+    if spread % 2 != time % 2 {
+        spread = spread.checked_sub(1)?;
+    }
 
-fn pressed_to_distance(total_time: IntType, pressed: IntType) -> IntType {
-    total_time.checked_sub(pressed).unwrap_or_default() * pressed
+    // spread is below time, so neither end overflows
+    let low = (time - spread) / 2;
+    let high = low + spread;
+    let narrow = |x: u128| T::try_from(x).ok().unwrap();
+    Some(narrow(low)..=narrow(high))
 }
 
 fn how_many_ways(time: IntType, dist: IntType) -> IntType {
-    let (mut low, mut high) = (0, time / 2);
-    while low != high {
-        let mid = low + (high - low) / 2;
-        if pressed_to_distance(time, mid) < dist {
-            low = mid + 1;
-        } else {
-            high = mid;
-        }
-    }
-    let smallest = low;
-
-    let (mut low, mut high) = (time / 2, time);
-    while low != high {
-        let mid = low + (high - low) / 2;
-        if pressed_to_distance(time, mid) >= dist {
-            low = mid + 1;
-        } else {
-            high = mid;
-        }
-    }
-    let largest = low - 1;
-
-    let smallest = if pressed_to_distance(time, smallest) > dist {
-        smallest
-    } else {
-        smallest + 1
-    };
-    let largest = if pressed_to_distance(time, largest) > dist {
-        largest
-    } else {
-        largest - 1
-    };
-
-    largest - smallest + 1
+    winning_holds(time, dist).map_or(0, |holds| holds.end() - holds.start() + 1)
 }
 
 pub fn solve_part_1(file: &str) -> Option<IntType> {
@@ -90,6 +79,68 @@ pub fn main(file: &String) {
 mod tests {
     use super::*;
     use crate::inputs::{self, InputType};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn pressed_to_distance(total_time: u128, pressed: u128) -> u128 {
+        total_time.saturating_sub(pressed) * pressed
+    }
+
+    // the hold times found by binary searching either side of the middle
+    fn binary_search_holds(time: u128, dist: u128) -> Option<(u128, u128)> {
+        if pressed_to_distance(time, time / 2) <= dist {
+            return None;
+        }
+        let (mut low, mut high) = (0, time / 2);
+        while low != high {
+            let mid = low + (high - low) / 2;
+            if pressed_to_distance(time, mid) <= dist {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let smallest = low;
+
+        let (mut low, mut high) = (time / 2, time);
+        while low != high {
+            let mid = low + (high - low) / 2;
+            if pressed_to_distance(time, mid) > dist {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Some((smallest, low - 1))
+    }
+
+    #[test]
+    fn finds_the_winning_holds() {
+        assert_eq!(winning_holds(7u64, 9), Some(2..=5));
+        assert_eq!(winning_holds(30u64, 200), Some(11..=19));
+        // a tie isn't a win
+        assert_eq!(winning_holds(4u64, 4), None);
+        assert_eq!(winning_holds(4u64, 3), Some(2..=2));
+        assert_eq!(winning_holds(0u64, 0), None);
+        assert_eq!(winning_holds(u64::MAX, 0), Some(1..=u64::MAX - 1));
+        assert_eq!(winning_holds(u128::MAX, u128::MAX), Some(2..=u128::MAX - 2));
+    }
+
+    #[test]
+    fn matches_binary_search() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..2000 {
+            let time: u128 = rng.gen_range(0..=u64::MAX as u128) >> rng.gen_range(0..64);
+            // mostly near the best distance, where the edges are
+            let best = (time / 2) * (time - time / 2);
+            let dist = best - best.min(rng.gen_range(0..=u64::MAX as u128) >> rng.gen_range(0..64));
+            let expected = binary_search_holds(time, dist);
+            assert_eq!(
+                winning_holds(time, dist).map(|holds| (*holds.start(), *holds.end())),
+                expected,
+                "time {time}, distance {dist}"
+            );
+        }
+    }
 
     #[test]
     fn solves_first_problem() {
@@ -1,197 +1,248 @@
-use std::collections::HashMap;
-use std::iter::zip;
-
-use once_cell::sync::Lazy;
-use regex::Regex;
-
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
-enum CardType {
-    Joker,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Jack,
-    Queen,
-    King,
-    Ace,
+use std::cmp::{Ordering, Reverse};
+
+use crate::helpers::parse::parse_lines;
+
+/// How hands that are in the same category are ranked.
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TieBreak {
+    /// the first card that's different decides, in the order they were dealt
+    InOrder,
+    /// like poker, the biggest group of cards is compared first, so a pair
+    /// of kings beats a pair of queens wherever they are
+    ByGroups,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
-enum HandType {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
+/// Which categories hands are sorted into, before any tie-break.
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Categories {
+    /// the sizes of the groups of matching cards, largest first, so five of
+    /// a kind is `[5]` and a full house `[3, 2]`. These compare largest group
+    /// first, which gives the usual order for any hand size.
+    Groups,
+    /// like poker, with a straight (every card one rank above the last, in
+    /// any order) between three of a kind and a full house. There are no
+    /// suits, so no flushes.
+    Poker,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Ord)]
-struct Hand {
-    cards: [CardType; 5],
-    bet: u32,
-    hand_type: HandType,
+/// The rules a game of camel cards is played with.
+///
+/// Wildcards all join the largest group, or fill the gaps in a straight.
+#[derive(Debug, Clone)]
+struct Rules {
+    /// the cards from weakest to strongest
+    order: Vec<char>,
+    wildcards: Vec<char>,
+    hand_size: usize,
+    categories: Categories,
+    tie_break: TieBreak,
 }
-impl Hand {
-    fn new(hand: &str, bet: &str, use_joker: bool) -> Self {
-        assert!(hand.len() == 5);
-        let cards: Vec<CardType> = hand
-            .chars()
-            .map(|c| match c {
-                'A' => CardType::Ace,
-                'K' => CardType::King,
-                'Q' => CardType::Queen,
-                'J' => match use_joker {
-                    true => CardType::Joker,
-                    false => CardType::Jack,
-                },
-                'T' => CardType::Ten,
-                '9' => CardType::Nine,
-                '8' => CardType::Eight,
-                '7' => CardType::Seven,
-                '6' => CardType::Six,
-                '5' => CardType::Five,
-                '4' => CardType::Four,
-                '3' => CardType::Three,
-                '2' => CardType::Two,
-                _ => panic!(),
-            })
+
+impl Rules {
+    /// Panics if a card is in the order twice.
+    fn new(order: &str) -> Self {
+        let order: Vec<char> = order.chars().collect();
+        for (k, c) in order.iter().enumerate() {
+            assert!(!order[k + 1..].contains(c), "{c} is in the order twice");
+        }
+        Rules {
+            order,
+            wildcards: vec![],
+            hand_size: 5,
+            categories: Categories::Groups,
+            tie_break: TieBreak::InOrder,
+        }
+    }
+
+    fn standard() -> Self {
+        Rules::new("23456789TJQKA")
+    }
+
+    /// Jacks are jokers, the weakest card but wild.
+    fn jokers() -> Self {
+        Rules::new("J23456789TQKA").wildcards("J")
+    }
+
+    /// Panics if a wildcard isn't in the order.
+    fn wildcards(mut self, wildcards: &str) -> Self {
+        self.wildcards = wildcards.chars().collect();
+        for c in &self.wildcards {
+            assert!(self.order.contains(c), "{c} is wild but not in the order");
+        }
+        self
+    }
+
+    #[allow(unused)]
+    fn hand_size(mut self, hand_size: usize) -> Self {
+        self.hand_size = hand_size;
+        self
+    }
+
+    #[allow(unused)]
+    fn categories(mut self, categories: Categories) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    #[allow(unused)]
+    fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    fn rank(&self, card: char) -> Option<u8> {
+        self.order.iter().position(|&c| c == card).map(|k| k as u8)
+    }
+
+    // the groups of matching cards that aren't wild, as (size, rank),
+    // biggest and then strongest first
+    fn groups(&self, cards: &[char]) -> Vec<(u8, u8)> {
+        let mut counts = vec![0u8; self.order.len()];
+        for &card in cards.iter().filter(|c| !self.wildcards.contains(c)) {
+            counts[self.rank(card).unwrap() as usize] += 1;
+        }
+        let mut groups: Vec<(u8, u8)> = (0..counts.len() as u8)
+            .map(|rank| (counts[rank as usize], rank))
+            .filter(|&(count, _)| count > 0)
             .collect();
+        groups.sort_by_key(|&group| Reverse(group));
+        groups
+    }
 
-        let mut card_array: [CardType; 5] = [CardType::Two; 5];
-        card_array.copy_from_slice(&cards);
+    fn category(&self, cards: &[char]) -> Vec<u8> {
+        let mut sizes: Vec<u8> = self.groups(cards).iter().map(|&(size, _)| size).collect();
+        let wild = cards.iter().filter(|c| self.wildcards.contains(c)).count() as u8;
+        match sizes.first_mut() {
+            Some(largest) => *largest += wild,
+            None => sizes.push(wild),
+        }
 
-        Hand {
-            cards: card_array,
-            bet: bet.parse().unwrap(),
-            hand_type: Hand::get_hand_type(card_array),
+        match self.categories {
+            Categories::Groups => sizes,
+            Categories::Poker => {
+                let tier = match sizes[..] {
+                    [5, ..] => 7,
+                    [4, ..] => 6,
+                    [3, 2, ..] => 5,
+                    _ if self.is_straight(cards) => 4,
+                    [3, ..] => 3,
+                    [2, 2, ..] => 2,
+                    [2, ..] => 1,
+                    _ => 0,
+                };
+                vec![tier]
+            }
+        }
+    }
+
+    // the cards that aren't wild are all different and close enough
+    // together for the wildcards to fill the gaps
+    fn is_straight(&self, cards: &[char]) -> bool {
+        let groups = self.groups(cards);
+        if groups.iter().any(|&(size, _)| size > 1) || cards.len() > self.order.len() {
+            return false;
+        }
+        let ranks = groups.iter().map(|&(_, rank)| rank as usize);
+        match (ranks.clone().min(), ranks.max()) {
+            (Some(low), Some(high)) => high - low < cards.len(),
+            _ => true,
+        }
+    }
+
+    // what's compared when two hands are in the same category
+    fn tie_break_key(&self, cards: &[char]) -> Vec<u8> {
+        match self.tie_break {
+            TieBreak::InOrder => cards.iter().map(|&c| self.rank(c).unwrap()).collect(),
+            TieBreak::ByGroups => {
+                let mut key: Vec<u8> = self
+                    .groups(cards)
+                    .iter()
+                    .flat_map(|&(size, rank)| std::iter::repeat_n(rank, size as usize))
+                    .collect();
+                // wildcards count as themselves here, after everything else
+                let mut wild: Vec<u8> = cards
+                    .iter()
+                    .filter(|c| self.wildcards.contains(c))
+                    .map(|&c| self.rank(c).unwrap())
+                    .collect();
+                wild.sort_by_key(|&rank| Reverse(rank));
+                key.extend(wild);
+                key
+            }
         }
     }
+}
+
+#[derive(Clone, Debug)]
+struct Hand {
+    bet: u32,
+    category: Vec<u8>,
+    tie_break_key: Vec<u8>,
+}
 
-    fn new_hands(file: &str, use_joker: bool) -> Vec<Hand> {
-        const REGEX: &str = r"^(?P<hand>\w{5}) (?P<bet>\d+)$";
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-        file.lines()
-            .map(|line| {
-                let x = RE.captures(line).unwrap();
-                let (_, [hand, bet]) = x.extract();
-                Hand::new(hand, bet, use_joker)
-            })
-            .collect()
-    }
-
-    fn get_hand_type(cards: [CardType; 5]) -> HandType {
-        let mut counts: HashMap<CardType, u8> = HashMap::new();
-        cards.iter().for_each(|&card| {
-            let count = counts.entry(card).or_default();
-            *count += 1;
-        });
-
-        let joker_count = *counts.get(&CardType::Joker).unwrap_or(&0);
-
-        match counts.values().max().unwrap() {
-            5 => HandType::FiveOfAKind,
-            4 => match joker_count {
-                4 | 1 => HandType::FiveOfAKind,
-                0 => HandType::FourOfAKind,
-                _ => panic!(),
-            },
-            3 => match joker_count {
-                3 => match counts.len() {
-                    3 => HandType::FourOfAKind,
-                    2 => HandType::FiveOfAKind,
-                    _ => panic!(),
-                },
-                2 => HandType::FiveOfAKind,
-                1 => HandType::FourOfAKind,
-                0 => match counts.len() {
-                    3 => HandType::ThreeOfAKind,
-                    2 => HandType::FullHouse,
-                    _ => panic!(),
-                },
-                _ => panic!(),
-            },
-            2 => match joker_count {
-                2 => match counts.len() {
-                    4 => HandType::ThreeOfAKind,
-                    3 => HandType::FourOfAKind,
-                    _ => panic!(),
-                },
-                1 => match counts.len() {
-                    4 => HandType::ThreeOfAKind,
-                    3 => HandType::FullHouse,
-                    _ => panic!(),
-                },
-                0 => match counts.len() {
-                    4 => HandType::OnePair,
-                    3 => HandType::TwoPair,
-                    _ => panic!(),
-                },
-                _ => panic!(),
-            },
-            1 => match joker_count {
-                1 => HandType::OnePair,
-                0 => HandType::HighCard,
-                _ => panic!(),
-            },
-            _ => panic!(),
+impl Hand {
+    fn new(cards: &str, bet: u32, rules: &Rules) -> Self {
+        let cards: Vec<char> = cards.chars().collect();
+        Hand {
+            category: rules.category(&cards),
+            tie_break_key: rules.tie_break_key(&cards),
+            bet,
         }
     }
+
+    fn new_hands(file: &str, rules: &Rules) -> Vec<Hand> {
+        parse_lines(file, |input| {
+            let cards = input.ident()?;
+            if cards.chars().count() != rules.hand_size {
+                return Err(input.error(format!("a hand of {} cards", rules.hand_size)));
+            }
+            if let Some(c) = cards.chars().find(|&c| rules.rank(c).is_none()) {
+                return Err(input.error(format!("a card, not {c:?}")));
+            }
+            input.literal(" ")?;
+            let bet = input.number()?;
+            Ok(Hand::new(cards, bet, rules))
+        })
+        .unwrap()
+    }
 }
 
 impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
-        if self.hand_type != other.hand_type {
-            return false;
-        }
-        zip(self.cards, other.cards).all(|(a, b)| a == b)
+        self.cmp(other) == Ordering::Equal
     }
 }
+impl Eq for Hand {}
 impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.hand_type.cmp(&other.hand_type) {
-            std::cmp::Ordering::Less => Some(std::cmp::Ordering::Less),
-            std::cmp::Ordering::Greater => Some(std::cmp::Ordering::Greater),
-            std::cmp::Ordering::Equal => {
-                for (a, b) in zip(self.cards, other.cards) {
-                    let comp = a.cmp(&b);
-                    if comp != std::cmp::Ordering::Equal {
-                        return Some(comp);
-                    }
-                }
-                Some(std::cmp::Ordering::Equal)
-            }
-        }
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.category
+            .cmp(&other.category)
+            .then_with(|| self.tie_break_key.cmp(&other.tie_break_key))
     }
 }
 
-pub fn solve_part_1(file: &str) -> Option<u32> {
-    let mut hands = Hand::new_hands(file, false);
+fn total_winnings(file: &str, rules: &Rules) -> u32 {
+    let mut hands = Hand::new_hands(file, rules);
     hands.sort();
-    Some(
-        hands
-            .iter()
-            .enumerate()
-            .fold(0, |z, (i, hand)| z + (hand.bet * (i as u32 + 1))),
-    )
+    hands
+        .iter()
+        .enumerate()
+        .fold(0, |z, (i, hand)| z + (hand.bet * (i as u32 + 1)))
+}
+
+pub fn solve_part_1(file: &str) -> Option<u32> {
+    Some(total_winnings(file, &Rules::standard()))
 }
 
 pub fn solve_part_2(file: &str) -> Option<u32> {
-    let mut hands = Hand::new_hands(file, true);
-    hands.sort();
-    Some(
-        hands
-            .iter()
-            .enumerate()
-            .fold(0, |z, (i, hand)| z + (hand.bet * (i as u32 + 1))),
-    )
+    Some(total_winnings(file, &Rules::jokers()))
 }
 
 const DAY: u8 = 7;
@@ -208,6 +259,61 @@ mod tests {
     use super::*;
     use crate::inputs::{self, InputType};
 
+    #[test]
+    fn finds_categories() {
+        let rules = Rules::standard();
+        let category = |cards: &str| Hand::new(cards, 0, &rules).category;
+        assert_eq!(category("AAAAA"), vec![5]);
+        assert_eq!(category("23332"), vec![3, 2]);
+        assert_eq!(category("23432"), vec![2, 2, 1]);
+        assert_eq!(category("23456"), vec![1, 1, 1, 1, 1]);
+
+        let rules = Rules::jokers();
+        let category = |cards: &str| Hand::new(cards, 0, &rules).category;
+        assert_eq!(category("QJJQ2"), vec![4, 1]);
+        assert_eq!(category("JJJJJ"), vec![5]);
+        assert_eq!(category("2345J"), vec![2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn plays_variants() {
+        // two kinds of wildcard, in hands of three
+        let rules = Rules::standard().wildcards("JQ").hand_size(3);
+        let hands = Hand::new_hands("2QJ 1\nA23 2\nKK2 3", &rules);
+        assert_eq!(hands[0].category, vec![3]);
+        assert!(hands[0] > hands[2] && hands[2] > hands[1]);
+
+        // the pair of kings wins, wherever it is
+        let in_order = Rules::standard();
+        let by_groups = Rules::standard().tie_break(TieBreak::ByGroups);
+        let (a, b) = ("A2233", "KKQQ2");
+        assert!(Hand::new(a, 0, &in_order) > Hand::new(b, 0, &in_order));
+        assert!(Hand::new(a, 0, &by_groups) < Hand::new(b, 0, &by_groups));
+    }
+
+    #[test]
+    fn ranks_like_poker() {
+        let rules = Rules::standard().categories(Categories::Poker);
+        let hand = |cards: &str| Hand::new(cards, 0, &rules);
+        assert!(hand("64352") > hand("AAAKQ"));
+        assert!(hand("64352") < hand("22233"));
+        assert!(hand("T9QJK") > hand("64352"));
+        // aces are only high
+        assert_eq!(hand("A2345").category, vec![0]);
+        assert!(hand("A2345") < hand("22AKQ"));
+
+        // a joker fills the gap
+        let rules = Rules::jokers().categories(Categories::Poker);
+        assert_eq!(Hand::new("2356J", 0, &rules).category, vec![4]);
+        assert_eq!(Hand::new("2357J", 0, &rules).category, vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "X is wild but not in the order")]
+    fn wildcards_have_to_be_cards() {
+        Rules::standard().wildcards("X");
+    }
+
     #[test]
     fn solves_first_problem() {
         let content = inputs::get_file(DAY, InputType::Sample);
@@ -1,90 +1,236 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use crate::helpers::math::lcm;
+use crate::helpers::cycle::find_cycle;
+use crate::helpers::math::{checked_lcm, crt, CrtError};
+use crate::helpers::parse::{sections, Input, ParseError, ParseErrorKind};
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
-struct NodeIdent {
-    ident: [u8; 3],
+/// The map, with every node label numbered from 0 in the order they're
+/// first seen.
+#[derive(Debug, Clone)]
+struct Network<'a> {
+    /// 0 for left and 1 for right
+    instructions: Vec<usize>,
+    labels: Vec<&'a str>,
+    index: HashMap<&'a str, usize>,
+    /// where left and right go from each node
+    next: Vec<[usize; 2]>,
 }
-impl NodeIdent {
-    fn from_slice(slice: &[u8]) -> Self {
-        let mut ident: [u8; 3] = [0; 3];
-        ident.copy_from_slice(&slice[..]);
-        Self { ident }
-    }
-    fn create_node_map(file: &str) -> (Vec<u8>, HashMap<Self, (Self, Self)>) {
-        let file: Vec<&str> = file.lines().collect();
-        let instructions = file[0].as_bytes();
-        let file = &file[2..];
-
-        let mut hashmap: HashMap<Self, (Self, Self)> = HashMap::new();
-        file.iter().for_each(|line| {
-            let line = line.as_bytes();
-            let ident = Self::from_slice(&line[0..3]);
-            let left = Self::from_slice(&line[7..10]);
-            let right = Self::from_slice(&line[12..15]);
-            hashmap.insert(ident, (left, right));
-        });
-
-        (instructions.to_vec(), hashmap)
-    }
-    fn at_exit(&self, full_exit: bool) -> bool {
-        match full_exit {
-            true => self.ident == *b"ZZZ",
-            false => self.ident[2] == b'Z',
-        }
+
+impl<'a> Network<'a> {
+    fn intern(&mut self, label: &'a str) -> usize {
+        *self.index.entry(label).or_insert_with(|| {
+            self.labels.push(label);
+            self.next.push([usize::MAX; 2]);
+            self.labels.len() - 1
+        })
     }
-    fn next_node(node: &Self, node_map: &HashMap<Self, (Self, Self)>, turn: u8) -> Self {
-        let (left, right) = node_map.get(&node).unwrap();
-        match turn {
-            b'L' => *left,
-            b'R' => *right,
-            _ => panic!(),
+
+    // LR
+    //
+    // AAA = (BBB, CCC)
+    fn parse(file: &'a str) -> Result<Self, ParseError> {
+        let sections = sections(file);
+        let [turns, nodes] = sections[..] else {
+            return Err(Input::new(file).error("the turns and the nodes"));
+        };
+        let instructions = turns
+            .parse_lines(|input| {
+                let mut instructions = vec![];
+                while !input.is_empty() {
+                    instructions.push(if input.one_of("LR")? == 'L' { 0 } else { 1 });
+                }
+                Ok(instructions)
+            })?
+            .concat();
+
+        let mut network = Network {
+            instructions,
+            labels: vec![],
+            index: HashMap::new(),
+            next: vec![],
+        };
+        let edges = nodes.parse_lines(|input| {
+            let node = input.ident()?;
+            input.literal(" = (")?;
+            let left = input.ident()?;
+            input.literal(", ")?;
+            let right = input.ident()?;
+            input.literal(")")?;
+            Ok((node, left, right))
+        })?;
+        for &(node, left, right) in &edges {
+            let node = network.intern(node);
+            network.next[node] = [network.intern(left), network.intern(right)];
         }
+
+        // every node that's gone to needs its own line
+        let described = |label: &str| network.next[network.index[label]][0] != usize::MAX;
+        for (k, (&(_, left, right), line)) in edges.iter().zip(nodes.lines()).enumerate() {
+            let (offset, label) = if !described(left) {
+                (line.find('(').unwrap() + 1, left)
+            } else if !described(right) {
+                (line.len() - 1 - right.len(), right)
+            } else {
+                continue;
+            };
+            return Err(ParseError {
+                kind: ParseErrorKind::Expected(format!("{label} to have a line of its own")),
+                line: Some(nodes.first_line + k),
+                column: line[..offset].chars().count() + 1,
+                found: label.to_string(),
+            });
+        }
+        Ok(network)
+    }
+
+    // one step along from `node`, using the `k`th instruction
+    fn step(&self, (node, k): &mut (usize, usize)) {
+        *node = self.next[*node][self.instructions[*k]];
+        *k = (*k + 1) % self.instructions.len();
+    }
+
+    fn nodes_ending_with(&self, c: char) -> impl Iterator<Item = usize> + '_ {
+        (0..self.labels.len()).filter(move |&node| self.labels[node].ends_with(c))
     }
 }
 
-fn dist_to_next_exit(
-    node: &NodeIdent,
-    node_map: &HashMap<NodeIdent, (NodeIdent, NodeIdent)>,
-    instructions: &[u8],
-    full_exit: bool,
-) -> u64 {
-    let mut cur = *node;
+fn dist_to_exit(network: &Network, start: usize, exit: usize) -> u64 {
+    let mut state = (start, 0);
     let mut count = 0;
-    while !cur.at_exit(full_exit) {
-        let turn = instructions[count % instructions.len()];
-        cur = NodeIdent::next_node(&cur, &node_map, turn);
+    while state.0 != exit {
+        network.step(&mut state);
         count += 1;
     }
-    count as u64
+    count
 }
 
 pub fn solve_part_1(file: &str) -> Option<u64> {
-    let (instructions, node_map) = NodeIdent::create_node_map(file);
-    Some(dist_to_next_exit(
-        &NodeIdent { ident: *b"AAA" },
-        &node_map,
-        &instructions,
-        true,
+    let network = Network::parse(file).unwrap();
+    Some(dist_to_exit(
+        &network,
+        *network.index.get("AAA")?,
+        *network.index.get("ZZZ")?,
     ))
 }
 
-pub fn solve_part_2(file: &str) -> Option<u64> {
-    let (instructions, node_map) = NodeIdent::create_node_map(file);
+/// Where a ghost's walk starts going round in a loop, and when it's at an
+/// exit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GhostWalk {
+    /// steps before the loop starts
+    tail: u64,
+    period: u64,
+    /// every step before the end of the first time round the loop that ends
+    /// on an exit
+    exits: Vec<u64>,
+}
 
-    let current_nodes: Vec<NodeIdent> = node_map
-        .keys()
-        .filter(|key| key.ident[2] == b'A')
-        .map(|n| *n)
-        .collect();
+impl GhostWalk {
+    fn new(network: &Network, start: usize) -> Self {
+        // the walk only repeats when the node and the place in the
+        // instructions both do
+        let cycle = find_cycle((start, 0), |state| network.step(state));
+
+        let mut state = (start, 0);
+        let mut exits = vec![];
+        for t in 0..cycle.start + cycle.period {
+            if network.labels[state.0].ends_with('Z') {
+                exits.push(t as u64);
+            }
+            network.step(&mut state);
+        }
+        GhostWalk {
+            tail: cycle.start as u64,
+            period: cycle.period as u64,
+            exits,
+        }
+    }
+
+    fn at_exit(&self, t: u64) -> bool {
+        let t = if t < self.tail {
+            t
+        } else {
+            self.tail + (t - self.tail) % self.period
+        };
+        self.exits.binary_search(&t).is_ok()
+    }
+
+    // when the ghost is at an exit once it's in the loop, mod the period
+    fn exits_in_loop(&self) -> impl Iterator<Item = u64> + '_ {
+        self.exits
+            .iter()
+            .filter(|&&t| t >= self.tail)
+            .map(|&t| t % self.period)
+    }
+}
+
+/// The first step where every ghost is at an exit at once.
+///
+/// Before every ghost is in its loop the steps are checked one by one. After
+/// that each ghost is at an exit on some steps mod its period, and those are
+/// combined one ghost at a time with the chinese remainder theorem. Choices
+/// that contradict each other are dropped straight away, and the ones left
+/// are steps mod the lcm of the periods so far, so there are never more of
+/// them than that lcm, or than the product of the exit counts.
+fn all_at_exits(walks: &[GhostWalk]) -> Result<u64, CrtError> {
+    let all_looping = walks.iter().map(|walk| walk.tail).max().unwrap_or(0);
+    if let Some(t) = (0..all_looping).find(|&t| walks.iter().all(|walk| walk.at_exit(t))) {
+        return Ok(t);
+    }
+
+    let (mut steps, mut modulus) = (BTreeSet::from([0]), 1);
+    for walk in walks {
+        let mut combined = BTreeSet::new();
+        for &x in &steps {
+            for exit in walk.exits_in_loop() {
+                match crt(&[(x, modulus), (exit, walk.period)]) {
+                    Ok((x, _)) => {
+                        combined.insert(x);
+                    }
+                    Err(CrtError::NoSolution) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        if combined.is_empty() {
+            return Err(CrtError::NoSolution);
+        }
+        steps = combined;
+        modulus = checked_lcm(modulus, walk.period).ok_or(CrtError::Overflow)?;
+    }
+
+    // the first time each happens once every ghost is looping
+    let mut first = u64::MAX;
+    for x in steps {
+        let t = if x >= all_looping {
+            x
+        } else {
+            (all_looping - x)
+                .div_ceil(modulus)
+                .checked_mul(modulus)
+                .and_then(|t| t.checked_add(x))
+                .ok_or(CrtError::Overflow)?
+        };
+        first = first.min(t);
+    }
+    Ok(first)
+}
 
-    let lcm = current_nodes.iter().fold(1, |z, node| {
-        let dist = dist_to_next_exit(node, &node_map, &instructions, false);
-        lcm(z, dist as usize)
-    });
+// every ghost's walk, with the label it starts from
+fn ghost_walks<'a>(network: &Network<'a>) -> Vec<(&'a str, GhostWalk)> {
+    network
+        .nodes_ending_with('A')
+        .map(|start| (network.labels[start], GhostWalk::new(network, start)))
+        .collect()
+}
 
-    Some(lcm as u64)
+pub fn solve_part_2(file: &str) -> Option<u64> {
+    let network = Network::parse(file).unwrap();
+    let walks: Vec<GhostWalk> = ghost_walks(&network)
+        .into_iter()
+        .map(|(_, walk)| walk)
+        .collect();
+    all_at_exits(&walks).ok()
 }
 
 const DAY: u8 = 8;
@@ -96,11 +242,42 @@ pub fn main(file: &str) {
     println!("  part 2: {:?}", solve_part_2(&file));
 }
 
+// like "11A: tail 1, loop 2, exits at 2"
+fn describe(label: &str, walk: &GhostWalk) -> String {
+    let exits: Vec<String> = walk.exits.iter().map(|t| t.to_string()).collect();
+    format!(
+        "{label}: tail {}, loop {}, exits at {}",
+        walk.tail,
+        walk.period,
+        if exits.is_empty() {
+            "none".to_string()
+        } else {
+            exits.join(", ")
+        }
+    )
+}
+
+/// Prints where each ghost's walk starts looping, how long the loop is and
+/// which steps end on an exit before the end of the first loop.
+pub fn report(file: &str) {
+    let network = Network::parse(file).unwrap();
+    for (label, walk) in ghost_walks(&network) {
+        println!("{}", describe(label, &walk));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::inputs::{self, InputType};
 
+    fn walks_of(network: &Network) -> Vec<GhostWalk> {
+        ghost_walks(network)
+            .into_iter()
+            .map(|(_, walk)| walk)
+            .collect()
+    }
+
     #[test]
     fn solves_first_problem() {
         let content = inputs::get_file_part(DAY, InputType::Sample, 1);
@@ -109,6 +286,99 @@ mod tests {
         assert_eq!(solve_part_1(&content), Some(6));
     }
 
+    #[test]
+    fn finds_ghost_loops() {
+        let content = inputs::get_file_part(DAY, InputType::Sample, 3);
+        let network = Network::parse(&content).unwrap();
+        let walks = walks_of(&network);
+        assert_eq!(
+            walks[0],
+            GhostWalk {
+                tail: 1,
+                period: 2,
+                exits: vec![2]
+            }
+        );
+        assert_eq!((walks[1].tail, walks[1].period), (1, 6));
+        assert_eq!(walks[1].exits, vec![3, 6]);
+    }
+
+    #[test]
+    fn works_without_lcm() {
+        // exits on odd steps, and on steps 2 mod 3, so lcm(1, 2) is wrong
+        let map = "L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)\n\
+                   22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22C, 22C)\n22C = (22B, 22B)";
+        assert_eq!(
+            all_at_exits(&walks_of(&Network::parse(map).unwrap())),
+            Ok(5)
+        );
+
+        // only at an exit before the loop starts
+        let map = "L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)\n\
+                   55A = (55Z, 55Z)\n55Z = (55B, 55B)\n55B = (55B, 55B)";
+        assert_eq!(
+            all_at_exits(&walks_of(&Network::parse(map).unwrap())),
+            Ok(1)
+        );
+
+        // odd steps and even steps never line up
+        let map = "L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)\n\
+                   44A = (44B, 44B)\n44B = (44Z, 44Z)\n44Z = (44B, 44B)";
+        assert_eq!(
+            all_at_exits(&walks_of(&Network::parse(map).unwrap())),
+            Err(CrtError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn interns_labels_of_any_length() {
+        let map = "RL\n\nstart = (start, middle)\nmiddle = (ZZZ, x)\nx = (x, x)\nZZZ = (ZZZ, ZZZ)";
+        let network = Network::parse(map).unwrap();
+        assert_eq!(network.labels, vec!["start", "middle", "ZZZ", "x"]);
+        assert_eq!(network.next[1], [2, 3]);
+        assert_eq!(network.instructions, vec![1, 0]);
+
+        let error = Network::parse("LR\n\nAAA = (BBB CCC)").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), 11));
+        assert!(Network::parse("LRX\n\nAAA = (AAA, AAA)").is_err());
+
+        let error = Network::parse("L\n\nAAA = (AAA, BBB)\nCCC = (DDD, CCC)").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), 13));
+        assert_eq!(error.found, "BBB");
+        let error = Network::parse("L\n\nAAA = (AAA, AAA)\nCCC = (DDD, DDD)").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(4), 8));
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 8: expected DDD to have a line of its own, found \"DDD\""
+        );
+    }
+
+    #[test]
+    fn reports_ghost_loops() {
+        let content = inputs::get_file_part(DAY, InputType::Sample, 3);
+        let network = Network::parse(&content).unwrap();
+        let lines: Vec<String> = ghost_walks(&network)
+            .iter()
+            .map(|(label, walk)| describe(label, walk))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "11A: tail 1, loop 2, exits at 2",
+                "22A: tail 1, loop 6, exits at 3, 6"
+            ]
+        );
+        let stuck = GhostWalk {
+            tail: 0,
+            period: 1,
+            exits: vec![],
+        };
+        assert_eq!(
+            describe("XXA", &stuck),
+            "XXA: tail 0, loop 1, exits at none"
+        );
+    }
+
     #[test]
     fn solves_second_problem() {
         let content = inputs::get_file_part(DAY, InputType::Sample, 3);
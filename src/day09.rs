@@ -1,37 +1,16 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use crate::helpers::constructor::line_to_digits;
+use crate::helpers::math::extrapolate_polynomial;
 
 type IntType = i32;
 
-const REGEX: &str = r"(-?\d+)";
-fn line_to_digits(line: &str) -> Vec<IntType> {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-    RE.find_iter(line)
-        .map(|m| m.as_str().trim_end().parse().unwrap_or(0))
-        .collect()
-}
-
 type Readings = Vec<IntType>;
 
-fn extrapolate_readings(readings: Readings) -> Vec<Readings> {
-    let mut stack: Vec<Readings> = vec![readings];
-    while !stack.last().unwrap().iter().all(|&reading| reading == 0) {
-        let prev = stack.last().unwrap();
-        let next: Readings = (0..prev.len() - 1).map(|i| prev[i + 1] - prev[i]).collect();
-        stack.push(next);
-    }
-    stack
-}
-
 fn next_data_point(readings: Readings) -> IntType {
-    let mut stack = extrapolate_readings(readings);
+    extrapolate_polynomial(&readings, readings.len() as IntType)
+}
 
-    stack.last_mut().unwrap().push(0);
-    (0..stack.len() - 1).rev().for_each(|i| {
-        let next_reading = stack[i].last().unwrap() + stack[i + 1].last().unwrap();
-        stack[i].push(next_reading)
-    });
-    *stack[0].last().unwrap()
+fn previous_data_point(readings: Readings) -> IntType {
+    extrapolate_polynomial(&readings, -1)
 }
 
 pub fn solve_part_1(file: &str) -> Option<IntType> {
@@ -44,12 +23,10 @@ pub fn solve_part_1(file: &str) -> Option<IntType> {
 }
 
 pub fn solve_part_2(file: &str) -> Option<IntType> {
-    // just reverse the list, how hard could it be?
     Some(
         file.lines()
             .map(|line| line_to_digits(line))
-            .map(|x| x.iter().rev().map(|x| *x).collect::<Vec<i32>>())
-            .map(|readings| next_data_point(readings))
+            .map(|readings| previous_data_point(readings))
             .sum(),
     )
 }
@@ -1,7 +1,9 @@
-use std::{
-    cmp::{max, min},
-    iter::zip,
-};
+use std::iter::zip;
+
+use crate::helpers::bitgrid::BitGrid;
+use crate::helpers::color::Color;
+use crate::helpers::geometry::interior_points;
+use crate::helpers::print_helpers::Canvas;
 
 type IntType = u32;
 
@@ -130,83 +132,80 @@ pub fn solve_part_1(file: &str) -> Option<IntType> {
     Some(count / 2)
 }
 
-fn grid_to_flood_grid((x, y): Position) -> Position {
-    (x * 2 + 1, y * 2 + 1)
-}
-fn midpoint_flooded(pos1: Position, pos2: Position) -> Position {
-    let (x1, y1) = pos1;
-    let (x2, y2) = pos2;
-    (
-        ((max(x1, x2) - min(x1, x2)) / 2 + min(x1, x2)),
-        ((max(y1, y2) - min(y1, y2)) / 2 + min(y1, y2)),
-    )
-}
-fn set_pipe_wall(flooder_grid: &mut Grid<bool>, pos: Position) {
-    let (x, y) = grid_to_flood_grid(pos);
-    flooder_grid[y][x] = true;
-}
-fn set_midpoint(flooder_grid: &mut Grid<bool>, pos1: Position, pos2: Position) {
-    let (pos1, pos2) = (grid_to_flood_grid(pos1), grid_to_flood_grid(pos2));
-    let (x, y) = midpoint_flooded(pos1, pos2);
-    flooder_grid[y][x] = true;
-}
-
-fn flood_fill(flooder_grid: &mut Grid<bool>, pos: Position) {
-    let (x, y) = pos;
-    flooder_grid[y][x] = true;
-
-    if x > 0 && (flooder_grid[y][x - 1] == false) {
-        flood_fill(flooder_grid, (x - 1, y));
-    }
-    if x < flooder_grid[y].len() - 1 && (flooder_grid[y][x + 1] == false) {
-        flood_fill(flooder_grid, (x + 1, y));
-    }
-
-    if y > 0 && (flooder_grid[y - 1][x] == false) {
-        flood_fill(flooder_grid, (x, y - 1));
-    }
-    if y < flooder_grid.len() - 1 && (flooder_grid[y + 1][x] == false) {
-        flood_fill(flooder_grid, (x, y + 1));
+// every tile of the loop, in the order they're gone through
+fn walk_loop(grid: &Grid<PipeType>, start_pos: Position) -> Vec<Position> {
+    let starting = PipeType::find_connections(grid, start_pos);
+    let mut traveler = (starting[0], start_pos);
+    let mut pipe_loop = vec![start_pos, traveler.0];
+    while traveler.0 != start_pos {
+        let (cur, prev) = traveler;
+        let next_pipe = PipeType::next_pipe(grid, cur, prev);
+        traveler.1 = cur;
+        traveler.0 = next_pipe;
+        pipe_loop.push(next_pipe);
     }
+    pipe_loop.pop();
+    pipe_loop
 }
 
 pub fn solve_part_2(file: &str) -> Option<IntType> {
     let (grid, start_pos) = PipeType::file_to_grid(file);
-    let mut flooder_grid: Grid<bool> = (0..grid.len() * 2 + 1)
-        .map(|_| (0..grid[0].len() * 2 + 1).map(|_| false).collect())
-        .collect();
-
-    let starting = PipeType::find_connections(&grid, start_pos);
-    let mut traveler = (starting[0], start_pos);
 
-    set_pipe_wall(&mut flooder_grid, traveler.0);
-    set_midpoint(&mut flooder_grid, traveler.0, traveler.1);
+    // every tile of the loop is a corner of the polygon it makes
+    let pipe_loop: Vec<(i64, i64)> = walk_loop(&grid, start_pos)
+        .into_iter()
+        .map(|(x, y)| (y as i64, x as i64))
+        .collect();
 
-    // place walls,
-    while traveler.0 != start_pos {
-        let (cur, prev) = traveler;
-        let next_pipe = PipeType::next_pipe(&grid, cur, prev);
-        traveler.1 = cur;
-        traveler.0 = next_pipe;
+    Some(interior_points(&pipe_loop) as IntType)
+}
 
-        set_pipe_wall(&mut flooder_grid, traveler.0);
-        set_midpoint(&mut flooder_grid, traveler.0, traveler.1);
+// the tiles inside the loop, found by flooding in from outside. The loop is
+// drawn at twice the size with a border, so the flood can squeeze between
+// pipes that don't connect.
+fn enclosed_tiles(grid: &Grid<PipeType>, pipe_loop: &[Position]) -> BitGrid {
+    let (rows, cols) = (grid.len(), grid[0].len());
+    let mut walls = BitGrid::new(rows * 2 + 1, cols * 2 + 1);
+    for (k, &(x, y)) in pipe_loop.iter().enumerate() {
+        let (next_x, next_y) = pipe_loop[(k + 1) % pipe_loop.len()];
+        walls.set((y * 2 + 1, x * 2 + 1), true);
+        walls.set((y + next_y + 1, x + next_x + 1), true);
     }
 
-    // flood fill from outside the map
-    flood_fill(&mut flooder_grid, (0, 0));
+    let mut open = walls.clone();
+    open.invert();
+    let outside = BitGrid::flood_fill(&open, (0, 0));
 
-    // count the number of un-flooded tiles, that align with the beginning grid
-    let mut count = 0;
-    for j in 0..grid.len() {
-        for i in 0..grid[j].len() {
-            let (x, y) = grid_to_flood_grid((i, j));
-            if flooder_grid[y][x] == false {
-                count += 1;
+    let mut enclosed = BitGrid::new(rows, cols);
+    for j in 0..rows {
+        for i in 0..cols {
+            let big = (j * 2 + 1, i * 2 + 1);
+            if outside.get(big) == Some(false) && walls.get(big) == Some(false) {
+                enclosed.set((j, i), true);
             }
         }
     }
-    Some(count)
+    enclosed
+}
+
+/// The loop, with the tiles it encloses highlighted.
+#[allow(unused)]
+pub fn render(file: &str) -> Canvas {
+    const INSIDE: Color = Color {
+        r: 60,
+        g: 200,
+        b: 80,
+    };
+    let (grid, start_pos) = PipeType::file_to_grid(file);
+    let pipe_loop = walk_loop(&grid, start_pos);
+
+    let mut on_loop = BitGrid::new(grid.len(), grid[0].len());
+    for &(x, y) in &pipe_loop {
+        on_loop.set((y, x), true);
+    }
+    let mut canvas = Canvas::from_grid(&on_loop.to_grid());
+    canvas.highlight(enclosed_tiles(&grid, &pipe_loop).iter_ones(), INSIDE);
+    canvas
 }
 
 const DAY: u8 = 10;
@@ -242,6 +241,23 @@ mod tests {
         assert_eq!(solve_part_1(&content), Some(8));
     }
 
+    #[test]
+    fn floods_the_same_tiles() {
+        for part in 5..=8 {
+            let content = inputs::get_file_part(DAY, InputType::Sample, part);
+            let (grid, start_pos) = PipeType::file_to_grid(&content);
+            let enclosed = enclosed_tiles(&grid, &walk_loop(&grid, start_pos));
+            assert_eq!(
+                Some(enclosed.count_ones() as IntType),
+                solve_part_2(&content)
+            );
+        }
+        let content = inputs::get_file_part(DAY, InputType::Sample, 5);
+        let canvas = render(&content);
+        assert!(canvas.cells()[(6, 2)].bg.is_some());
+        assert!(canvas.cells()[(0, 0)].bg.is_none());
+    }
+
     #[test]
     fn solves_second_problem() {
         let content = inputs::get_file_part(DAY, InputType::Sample, 5);
@@ -1,20 +1,10 @@
-use std::collections::HashMap;
-
 use rayon::prelude::*;
 
-use once_cell::sync::Lazy;
-use regex::Regex;
+use crate::helpers::constructor::line_to_digits;
+use crate::helpers::memo::{CacheStats, Memo};
 
 type IntType = u64;
 
-fn line_to_digits(line: &str) -> Vec<u8> {
-    const REGEX: &str = r"(-?\d+)";
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-    RE.find_iter(line)
-        .map(|m| m.as_str().trim_end().parse().unwrap_or(0))
-        .collect()
-}
-
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum State {
     Operational,
@@ -32,97 +22,69 @@ impl State {
     }
 }
 
-fn recur_all_permutations(
-    n: u8,
-    m: u8,
-    run: u8,
+// how many ways the unknown springs can be filled in, `damaged_left` is how
+// many of them have to be damaged
+fn count_arrangements(
     states: &[State],
     run_lens: &[u8],
-    hashmap: &mut HashMap<[u8; 4], IntType>,
-) -> IntType {
-    if hashmap.contains_key(&[n, m, run, run_lens.len() as u8]) {
-        hashmap[&[n, m, run, run_lens.len() as u8]]
-    } else {
-        let answer = if states.len() == 0 {
-            if run > 0 {
-                if run_lens.len() == 0 || run != run_lens[0] {
-                    0
+    damaged_left: u8,
+) -> (IntType, CacheStats) {
+    // (index into states, damaged left, current run, index into run_lens)
+    let mut memo = Memo::new(
+        |recur: &mut dyn FnMut((usize, u8, u8, usize)) -> IntType,
+         (i, m, run, k): (usize, u8, u8, usize)| {
+            let (states, run_lens) = (&states[i..], &run_lens[k..]);
+            if states.is_empty() {
+                return if run > 0 {
+                    (run_lens == [run]) as IntType
                 } else {
-                    if run_lens.len() == 1 {
-                        1
-                    } else {
-                        0
-                    }
-                }
-            } else {
-                if run_lens.len() == 0 {
-                    1
-                } else {
-                    0
-                }
+                    run_lens.is_empty() as IntType
+                };
             }
-        } else {
+
             match states[0] {
                 State::Operational => {
                     if run > 0 {
-                        if run_lens.len() == 0 || run != run_lens[0] {
+                        if run_lens.first() != Some(&run) {
                             0
                         } else {
-                            recur_all_permutations(n, m, 0, &states[1..], &run_lens[1..], hashmap)
+                            recur((i + 1, m, 0, k + 1))
                         }
                     } else {
-                        recur_all_permutations(n, m, 0, &states[1..], run_lens, hashmap)
+                        recur((i + 1, m, 0, k))
                     }
                 }
                 State::Damaged => {
-                    if run_lens.len() == 0 || run + 1 > run_lens[0] {
+                    if run_lens.first().is_none_or(|&len| run + 1 > len) {
                         0
                     } else {
-                        recur_all_permutations(n, m, run + 1, &states[1..], run_lens, hashmap)
+                        recur((i + 1, m, run + 1, k))
                     }
                 }
                 State::Unknown => {
-                    let yes = if m > 0 {
-                        if run < run_lens[0] {
-                            recur_all_permutations(
-                                n - 1,
-                                m - 1,
-                                run + 1,
-                                &states[1..],
-                                run_lens,
-                                hashmap,
-                            )
-                        } else {
-                            0
-                        }
+                    let yes = if m > 0 && run_lens.first().is_some_and(|&len| run < len) {
+                        recur((i + 1, m - 1, run + 1, k))
                     } else {
                         0
                     };
 
                     let no = if run > 0 {
-                        if run == run_lens[0] {
-                            recur_all_permutations(
-                                n - 1,
-                                m,
-                                0,
-                                &states[1..],
-                                &run_lens[1..],
-                                hashmap,
-                            )
+                        if run_lens.first() == Some(&run) {
+                            recur((i + 1, m, 0, k + 1))
                         } else {
                             0
                         }
                     } else {
-                        recur_all_permutations(n - 1, m, 0, &states[1..], run_lens, hashmap)
+                        recur((i + 1, m, 0, k))
                     };
 
                     yes + no
                 }
             }
-        };
-        hashmap.insert([n, m, run, run_lens.len() as u8], answer);
-        answer
-    }
+        },
+    );
+    let arrangements = memo.get((0, damaged_left, 0, 0));
+    (arrangements, memo.stats())
 }
 
 struct Record {
@@ -138,18 +100,11 @@ impl Record {
 
         Record { states, numbers }
     }
-    fn num_unknown(&self) -> usize {
-        self.states
-            .iter()
-            .filter(|state| **state == State::Unknown)
-            .count()
-    }
     fn number_of_arrangements(&self) -> IntType {
-        let n = self.num_unknown() as u8;
         let m = self.numbers.iter().sum::<u8>()
             - self.states.iter().filter(|x| **x == State::Damaged).count() as u8;
 
-        recur_all_permutations(n, m, 0, &self.states, &self.numbers, &mut HashMap::new())
+        count_arrangements(&self.states, &self.numbers, m).0
     }
 }
 
@@ -208,4 +163,13 @@ mod tests {
         let content = inputs::get_file(DAY, InputType::Sample);
         assert_eq!(solve_part_2(&content), Some(525152))
     }
+
+    #[test]
+    fn reuses_cached_arrangements() {
+        let record = Record::line_to_record("?###???????? 3,2,1");
+        let m = 6 - 3;
+        let (arrangements, stats) = count_arrangements(&record.states, &record.numbers, m);
+        assert_eq!(arrangements, 10);
+        assert!(stats.hits > 0);
+    }
 }
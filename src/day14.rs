@@ -1,5 +1,9 @@
 use rayon::prelude::*;
-use std::{collections::HashMap, hash::Hash};
+use std::hash::Hash;
+
+use crate::helpers::cycle::fast_forward;
+use crate::helpers::print_helpers::{Canvas, ToChar};
+use crate::helpers::trace::{Trace, Tracer};
 
 type IntType = u32;
 
@@ -19,6 +23,15 @@ impl RockType {
         }
     }
 }
+impl ToChar for RockType {
+    fn to_char(&self) -> char {
+        match self {
+            RockType::Rounded => 'O',
+            RockType::Cube => '#',
+            RockType::None => '.',
+        }
+    }
+}
 
 fn file_to_grid(file: &str) -> Vec<Vec<RockType>> {
     let grid: Vec<Vec<RockType>> = file
@@ -103,9 +116,10 @@ fn tilt(grid: &mut Vec<Vec<RockType>>, tilt: Tilt) {
 }
 
 const CYCLE: [Tilt; 4] = [Tilt::North, Tilt::West, Tilt::South, Tilt::East];
-fn cycle_grid_vec(grid: &mut Vec<Vec<RockType>>) {
+fn cycle_grid_vec(grid: &mut Vec<Vec<RockType>>, tracer: &mut impl Tracer) {
     for t in CYCLE {
-        tilt(grid, t)
+        tilt(grid, t);
+        tracer.record(|| Canvas::from_rows(grid));
     }
 }
 
@@ -129,28 +143,11 @@ pub fn solve_part_1(file: &str) -> Option<IntType> {
 
 pub fn solve_part_2(file: &str) -> Option<IntType> {
     const CYCLES: usize = 1_000_000_000;
-    let mut grid = file_to_grid(file);
-    let mut hashmap = HashMap::new();
-    hashmap.insert(grid.clone(), 0);
-
-    let mut i = 1;
-    while i < CYCLES {
-        cycle_grid_vec(&mut grid);
-        if hashmap.contains_key(&grid) {
-            break;
-        }
-        hashmap.insert(grid.clone(), i);
-        i += 1;
-    }
-
-    let j = hashmap[&grid];
-
-    // cycle some amounts of time more
-    let cycle_length = i - j;
-    let extra_cycles = (CYCLES - j) % cycle_length;
-    for _ in 0..extra_cycles {
-        cycle_grid_vec(&mut grid);
-    }
+    let grid = fast_forward(
+        file_to_grid(file),
+        |grid| cycle_grid_vec(grid, &mut ()),
+        CYCLES,
+    );
 
     Some(count_load(&grid))
 }
@@ -164,9 +161,22 @@ pub fn main(file: &str) {
     println!("  part 2: {:?}", solve_part_2(&file));
 }
 
+/// Every tilt of the first few spin cycles.
+#[allow(unused)]
+pub fn trace(file: &str, cycles: usize) -> Trace {
+    let mut grid = file_to_grid(file);
+    let mut trace = Trace::new();
+    trace.record(|| Canvas::from_rows(&grid));
+    for _ in 0..cycles {
+        cycle_grid_vec(&mut grid, &mut trace);
+    }
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helpers::cycle::{find_cycle, Cycle};
     use crate::inputs::{self, InputType};
 
     #[test]
@@ -180,4 +190,24 @@ mod tests {
         let content = inputs::get_file(DAY, InputType::Sample);
         assert_eq!(solve_part_2(&content), Some(64))
     }
+
+    #[test]
+    fn finds_the_spin_cycle() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let cycle = find_cycle(file_to_grid(&content), |grid| cycle_grid_vec(grid, &mut ()));
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 3,
+                period: 7
+            }
+        );
+    }
+
+    #[test]
+    fn traces_every_tilt() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let trace = trace(&content, 3);
+        assert_eq!(trace.frames().len(), 1 + 3 * 4);
+    }
 }
@@ -1,7 +1,10 @@
 use rayon::prelude::*;
 
+use crate::helpers::color::Color;
 use crate::helpers::constructor::{file_to_grid, FromChar, Grid};
 use crate::helpers::enums_and_types::Direction;
+use crate::helpers::print_helpers::{Canvas, ToChar};
+use crate::helpers::trace::{Trace, Tracer};
 
 type IntType = u32;
 type Position = (usize, usize);
@@ -26,6 +29,17 @@ impl FromChar for MirrorType {
         }
     }
 }
+impl ToChar for MirrorType {
+    fn to_char(&self) -> char {
+        match self {
+            MirrorType::None => '.',
+            MirrorType::ReflectForward => '/',
+            MirrorType::ReflectBack => '\\',
+            MirrorType::SplitPipe => '|',
+            MirrorType::SplitDash => '-',
+        }
+    }
+}
 impl MirrorType {
     fn reflected_to(self, dir: Direction) -> Vec<Direction> {
         match self {
@@ -106,13 +120,31 @@ fn next_checks(
         .collect()
 }
 
-fn calculate_energized(mirrors: &Grid<MirrorType>, start: (Position, Direction)) -> IntType {
+fn calculate_energized(
+    mirrors: &Grid<MirrorType>,
+    start: (Position, Direction),
+    tracer: &mut impl Tracer,
+) -> IntType {
+    const ENERGIZED: Color = Color {
+        r: 230,
+        g: 180,
+        b: 40,
+    };
     let mut energy_grid: Grid<Vec<Direction>> = Grid::new(mirrors.rows(), mirrors.cols());
     let mut light_stack: Vec<(Position, Direction)> = vec![start];
     while let Some((pos, dir)) = light_stack.pop() {
         if !energy_grid[pos].contains(&dir) {
             energy_grid[pos].push(dir);
-            light_stack.append(&mut next_checks(&mirrors, (pos, dir)))
+            light_stack.append(&mut next_checks(&mirrors, (pos, dir)));
+            tracer.record(|| {
+                let mut canvas = Canvas::from_grid(mirrors);
+                let energized = energy_grid
+                    .indexed_iter()
+                    .filter(|(_, dirs)| !dirs.is_empty())
+                    .map(|(pos, _)| pos);
+                canvas.highlight(energized, ENERGIZED);
+                canvas
+            });
         }
     }
     energy_grid.iter().filter(|dirs| !dirs.is_empty()).count() as IntType
@@ -122,6 +154,7 @@ pub fn solve_part_1(file: &str) -> Option<IntType> {
     Some(calculate_energized(
         &file_to_grid(file),
         ((0, 0), Direction::Right),
+        &mut (),
     ))
 }
 
@@ -143,7 +176,7 @@ pub fn solve_part_2(file: &str) -> Option<IntType> {
     Some(
         starts
             .par_iter()
-            .map(|start| calculate_energized(&mirrors, *start))
+            .map(|start| calculate_energized(&mirrors, *start, &mut ()))
             .max()
             .unwrap(),
     )
@@ -158,6 +191,14 @@ pub fn main(file: &str) {
     println!("  part 2: {:?}", solve_part_2(&file));
 }
 
+/// The beam from part 1, one frame for every tile and direction it reaches.
+#[allow(unused)]
+pub fn trace(file: &str) -> Trace {
+    let mut trace = Trace::new();
+    calculate_energized(&file_to_grid(file), ((0, 0), Direction::Right), &mut trace);
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,4 +215,13 @@ mod tests {
         let content = inputs::get_file(DAY, InputType::Sample);
         assert_eq!(solve_part_2(&content), Some(51))
     }
+
+    #[test]
+    fn traces_the_beam() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let trace = trace(&content);
+        let last = trace.frames().last().unwrap();
+        let energized = last.cells().iter().filter(|cell| cell.bg.is_some()).count();
+        assert_eq!(energized, 46);
+    }
 }
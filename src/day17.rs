@@ -1,5 +1,6 @@
 use crate::helpers::constructor::{file_to_grid, FromChar, Grid};
 use crate::helpers::enums_and_types::{Direction, Position};
+use crate::helpers::print_helpers::Canvas;
 use std::collections::BinaryHeap;
 
 type IntType = u32;
@@ -145,8 +146,11 @@ impl PartialOrd for NodeState {
     }
 }
 
-fn path_find(file: &str, (min_dist, max_dist): (u8, u8)) -> IntType {
+// the least heat lost on the way to the end, and on the way to each block
+// the search got to before then
+fn search(file: &str, (min_dist, max_dist): (u8, u8)) -> (IntType, Grid<Option<IntType>>) {
     let loss_grid: Grid<u8> = file_to_grid(file);
+    let mut reached: Grid<Option<IntType>> = Grid::new(loss_grid.rows(), loss_grid.cols());
 
     let mut lowest_heat_on: Grid<Vec<DirAndCount>> = Grid::new(loss_grid.rows(), loss_grid.cols());
 
@@ -161,13 +165,14 @@ fn path_find(file: &str, (min_dist, max_dist): (u8, u8)) -> IntType {
         if (state.position == (loss_grid.rows() - 1, loss_grid.cols() - 1))
             && (state.dir_and_count.1 >= min_dist)
         {
-            return state.heat_level;
+            return (state.heat_level, reached);
         }
 
         if lowest_heat_on[state.position].contains(&state.dir_and_count) {
             continue;
         } else {
             lowest_heat_on[state.position].push(state.dir_and_count);
+            reached[state.position].get_or_insert(state.heat_level);
         }
 
         next_checks(&loss_grid, (min_dist, max_dist), state)
@@ -177,6 +182,18 @@ fn path_find(file: &str, (min_dist, max_dist): (u8, u8)) -> IntType {
     panic!()
 }
 
+fn path_find(file: &str, min_and_max: (u8, u8)) -> IntType {
+    search(file, min_and_max).0
+}
+
+/// The least heat lost getting to each block, blue for the least and red
+/// for the most, with the blocks the search never got to left as `.`.
+#[allow(unused)]
+pub fn render(file: &str) -> Canvas {
+    let (_, reached) = search(file, (0, 3));
+    Canvas::heatmap(&reached, |heat| heat.map(|heat| heat as f64))
+}
+
 pub fn solve_part_1(file: &str) -> Option<IntType> {
     Some(path_find(file, (0, 3)))
 }
@@ -197,6 +214,7 @@ pub fn main(file: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helpers::print_helpers::heat_color;
     use crate::inputs::{self, InputType};
 
     #[test]
@@ -205,6 +223,15 @@ mod tests {
         assert_eq!(solve_part_1(&content), Some(102));
     }
 
+    #[test]
+    fn renders_heat_loss() {
+        let content = inputs::get_file_part(DAY, InputType::Sample, 1);
+        let canvas = render(&content);
+        let cells = canvas.cells();
+        assert_eq!(cells[(0, 0)].bg, Some(heat_color(0.0)));
+        assert!(cells.iter().filter(|cell| cell.bg.is_some()).count() > 100);
+    }
+
     #[test]
     fn solves_second_problem() {
         let content = inputs::get_file_part(DAY, InputType::Sample, 1);
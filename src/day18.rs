@@ -1,6 +1,13 @@
 use crate::helpers::{
-    color::hex_to_bin, enums_and_types::Direction, positions_and_directions::next_position_counted,
+    color::{hex_to_bin, Color},
+    enums_and_types::Direction,
+    geometry::{
+        boundary_points, interior_points, locate_point, rectilinear_polygon, Point, PointLocation,
+    },
+    positions_and_directions::next_position_counted,
+    print_helpers::Canvas,
 };
+use grid::Grid;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
@@ -10,13 +17,14 @@ type IntType = i64;
 struct DigStep {
     dir: Direction,
     count: IntType,
+    color: Color,
 }
 impl DigStep {
     fn line_to_step(line: &str, correct: bool) -> Self {
-        const REGEX: &str =
-            r"(?P<dir>\w) (?P<count>\d+) \(#(?P<count2>[[:xdigit:]]{5})(?P<dir2>[[:xdigit:]])\)";
+        const REGEX: &str = r"(?P<dir>\w) (?P<count>\d+) \((?P<color>#(?P<count2>[[:xdigit:]]{5})(?P<dir2>[[:xdigit:]]))\)";
         static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-        let (_, [dir, count, count_true, dir_true]) = RE.captures(line).unwrap().extract();
+        let (_, [dir, count, color, count_true, dir_true]) = RE.captures(line).unwrap().extract();
+        let color = Color::from_string(color).unwrap();
 
         match correct {
             false => DigStep {
@@ -28,6 +36,7 @@ impl DigStep {
                     _ => panic!(),
                 },
                 count: count.parse().unwrap(),
+                color,
             },
             true => DigStep {
                 dir: match dir_true {
@@ -37,7 +46,8 @@ impl DigStep {
                     "3" => Direction::Up,
                     _ => panic!(),
                 },
-                count: hex_to_bin(count_true) as IntType,
+                count: hex_to_bin(count_true).unwrap() as IntType,
+                color,
             },
         }
     }
@@ -49,19 +59,51 @@ impl DigStep {
 }
 
 fn get_interior_volume(steps: Vec<DigStep>) -> IntType {
-    // use polygon area formula, inputs to big
-    let mut area = 0;
-    let mut line = 0;
-    steps.iter().fold((0, 0), |(row, col), item| {
-        let (next_row, next_col) = next_position_counted((row, col), item.dir, item.count);
+    // inputs to big to flood fill, so count the lattice points instead
+    let trench = rectilinear_polygon((0, 0), steps.iter().map(|step| (step.dir, step.count)));
+    interior_points(&trench) + boundary_points(&trench)
+}
+
+/// Every dug out tile of the trench, with the colour it was painted.
+#[allow(unused)]
+pub fn trench_colors(file: &str) -> Vec<(Point, Color)> {
+    let mut pos: Point = (0, 0);
+    let mut tiles = vec![];
+    for step in DigStep::file_to_steps(file, false) {
+        for k in 1..=step.count {
+            tiles.push((next_position_counted(pos, step.dir, k), step.color));
+        }
+        pos = next_position_counted(pos, step.dir, step.count);
+    }
+    tiles
+}
 
-        area += (col * next_row) - (row * next_col);
-        line += item.count;
+/// The part 1 lagoon, with the trench drawn in its colours.
+#[allow(unused)]
+pub fn render(file: &str) -> Canvas {
+    let steps = DigStep::file_to_steps(file, false);
+    let trench = rectilinear_polygon((0, 0), steps.iter().map(|step| (step.dir, step.count)));
+    let min_j = trench.iter().map(|p| p.0).min().unwrap();
+    let min_i = trench.iter().map(|p| p.1).min().unwrap();
+    let max_j = trench.iter().map(|p| p.0).max().unwrap();
+    let max_i = trench.iter().map(|p| p.1).max().unwrap();
 
-        (next_row, next_col)
-    });
+    let rows = (max_j - min_j + 1) as usize;
+    let cols = (max_i - min_i + 1) as usize;
+    let mut lagoon: Grid<bool> = Grid::new(rows, cols);
+    for j in 0..rows {
+        for i in 0..cols {
+            let point = (j as IntType + min_j, i as IntType + min_i);
+            lagoon[(j, i)] = locate_point(&trench, point) != PointLocation::Outside;
+        }
+    }
 
-    area / 2 + line / 2 + 1
+    let mut canvas = Canvas::from_grid(&lagoon);
+    for ((j, i), color) in trench_colors(file) {
+        let pos = ((j - min_j) as usize, (i - min_i) as usize);
+        canvas.highlight([pos], color);
+    }
+    canvas
 }
 
 pub fn solve_part_1(file: &str) -> Option<IntType> {
@@ -99,4 +141,16 @@ mod tests {
         let content = inputs::get_file(DAY, InputType::Sample);
         assert_eq!(solve_part_2(&content), Some(952408144115))
     }
+
+    #[test]
+    fn keeps_trench_colors() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let tiles = trench_colors(&content);
+        assert_eq!(tiles.len(), 38);
+        assert_eq!(tiles[0], ((0, 1), Color::from_string("#70c710").unwrap()));
+
+        let canvas = render(&content);
+        assert_eq!(canvas.cells().iter().filter(|c| c.c == '#').count(), 62);
+        assert_eq!(canvas.cells().iter().filter(|c| c.bg.is_some()).count(), 38);
+    }
 }
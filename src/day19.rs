@@ -1,16 +1,12 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::collections::HashMap;
 
-use std::{
-    cmp::{max, min},
-    collections::HashMap,
-};
-
-use crate::helpers::constructor::FromChar;
+use crate::helpers::intervals::IntervalBox;
+use crate::helpers::parse::{sections, Input, ParseError};
 
 type RangeInt = u16;
 type IntType = u64;
 
+// the order is the axis of each property in `Ranges`
 #[derive(Debug, Clone, Copy)]
 enum Property {
     X,
@@ -18,14 +14,14 @@ enum Property {
     A,
     S,
 }
-impl FromChar for Property {
-    fn from_char(c: char) -> Self {
-        match c {
-            'x' => Property::X,
-            'm' => Property::M,
-            'a' => Property::A,
-            's' => Property::S,
-            _ => panic!(),
+impl Property {
+    fn new(name: &str) -> Option<Self> {
+        match name {
+            "x" => Some(Property::X),
+            "m" => Some(Property::M),
+            "a" => Some(Property::A),
+            "s" => Some(Property::S),
+            _ => None,
         }
     }
 }
@@ -45,33 +41,41 @@ struct WorkFlow {
     otherwise: String,
 }
 impl WorkFlow {
-    fn new(line: &str) -> Self {
-        const START_AND_END: &str = r"(?P<ident>\w+)\{.*,(?P<end_ident>\w+)\}";
-        static RE_S_A_E: Lazy<Regex> = Lazy::new(|| Regex::new(START_AND_END).unwrap());
-        let (_, [ident, end_ident]) = RE_S_A_E.captures(line).unwrap().extract();
-
-        const INSTRUCTIONS: &str = r"(?P<part>[xmas])(?P<cmp>[<>])(?P<num>\d+):(?P<end>\w+)";
-        static RE_INS: Lazy<Regex> = Lazy::new(|| Regex::new(INSTRUCTIONS).unwrap());
-        let instructions = RE_INS
-            .captures_iter(line)
-            .map(|c| c.extract())
-            .map(|(_, [part, cmp, num, end])| Instruction {
-                property: Property::from_char(part.chars().last().unwrap()),
-                is_lesser: match cmp {
-                    "<" => true,
-                    ">" => false,
-                    _ => panic!(),
-                },
-                number: num.parse().unwrap(),
-                output: end.to_string(),
-            })
-            .collect();
+    // px{a<2006:qkq,m>2090:A,rfg}
+    fn parse(input: &mut Input) -> Result<Self, ParseError> {
+        let ident = input.ident()?.to_string();
+        input.literal("{")?;
+
+        let mut instructions = vec![];
+        let otherwise = loop {
+            let name = input.ident()?;
+            let is_lesser = if input.eat("<") {
+                true
+            } else if input.eat(">") {
+                false
+            } else {
+                // the last one is only where to go
+                break name.to_string();
+            };
+            let property = Property::new(name).ok_or_else(|| input.error("x, m, a or s"))?;
+            let number = input.number()?;
+            input.literal(":")?;
+            let output = input.ident()?.to_string();
+            input.literal(",")?;
+            instructions.push(Instruction {
+                property,
+                is_lesser,
+                number,
+                output,
+            });
+        };
+        input.literal("}")?;
 
-        WorkFlow {
-            ident: ident.to_string(),
-            otherwise: end_ident.to_string(),
+        Ok(WorkFlow {
+            ident,
             instructions,
-        }
+            otherwise,
+        })
     }
 }
 
@@ -83,80 +87,38 @@ struct Part {
     s: RangeInt,
 }
 impl Part {
-    fn new(line: &str) -> Self {
-        const REGEX_PART: &str = r"\{x=(?P<x>\d+),m=(?P<m>\d+),a=(?P<a>\d+),s=(?P<s>\d+)\}";
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX_PART).unwrap());
-        let (_, [x, m, a, s]) = RE.captures(line).unwrap().extract();
-        Part {
-            x: x.parse().unwrap(),
-            m: m.parse().unwrap(),
-            s: s.parse().unwrap(),
-            a: a.parse().unwrap(),
-        }
+    // {x=787,m=2655,a=1222,s=2876}
+    fn parse(input: &mut Input) -> Result<Self, ParseError> {
+        input.literal("{x=")?;
+        let x = input.number()?;
+        input.literal(",m=")?;
+        let m = input.number()?;
+        input.literal(",a=")?;
+        let a = input.number()?;
+        input.literal(",s=")?;
+        let s = input.number()?;
+        input.literal("}")?;
+        Ok(Part { x, m, a, s })
     }
     fn get_total(&self) -> RangeInt {
         self.x + self.m + self.a + self.s
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Range {
-    start: RangeInt,
-    end: RangeInt,
-}
-impl Range {
-    fn new(start: RangeInt, end: RangeInt) -> Self {
-        Range { start, end }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Ranges {
-    x: Range,
-    m: Range,
-    a: Range,
-    s: Range,
-}
-impl Ranges {
-    fn get_part(&self, property: Property) -> Range {
-        match property {
-            Property::X => self.x,
-            Property::M => self.m,
-            Property::A => self.a,
-            Property::S => self.s,
-        }
-    }
-    fn get_part_mut(&mut self, property: Property) -> &mut Range {
-        match property {
-            Property::X => &mut self.x,
-            Property::M => &mut self.m,
-            Property::A => &mut self.a,
-            Property::S => &mut self.s,
-        }
-    }
-    fn replace_part(mut self, property: Property, new_r: Range) -> Self {
-        *self.get_part_mut(property) = new_r;
-        self
-    }
-    fn number_permutations(&self) -> IntType {
-        const PROPERTIES: [Property; 4] = [Property::X, Property::M, Property::A, Property::S];
-        PROPERTIES.iter().fold(1, |z, &u| {
-            let r = self.get_part(u);
-            z * (r.end - r.start) as IntType
-        })
-    }
-}
+type Ranges = IntervalBox<RangeInt, 4>;
 
 fn parse_file(file: &str) -> (HashMap<String, WorkFlow>, Vec<Part>) {
-    let (works, parts) = file.split_once("\n\n").unwrap();
+    let [works, parts] = sections(file)[..] else {
+        panic!("expected work flows and parts");
+    };
 
-    let work_flows: Vec<WorkFlow> = works.lines().map(|line| WorkFlow::new(line)).collect();
+    let work_flows = works.parse_lines(WorkFlow::parse).unwrap();
     let mut hashmap: HashMap<String, WorkFlow> = HashMap::new();
     for work_flow in work_flows {
-        hashmap.insert(work_flow.ident.clone(), work_flow.clone());
+        hashmap.insert(work_flow.ident.clone(), work_flow);
     }
 
-    let parts = parts.lines().map(|line| Part::new(line)).collect();
+    let parts = parts.parse_lines(Part::parse).unwrap();
 
     (hashmap, parts)
 }
@@ -169,45 +131,27 @@ fn recur_stack(hashmap: &HashMap<String, WorkFlow>, ranges: Ranges) -> IntType {
         let work_flow = match ident {
             "R" => continue 'recur,
             "A" => {
-                total += ranges.number_permutations();
+                total += ranges.volume::<IntType>();
                 continue 'recur;
             }
             ident => &hashmap[ident],
         };
 
         for ins in work_flow.instructions.iter() {
-            let r = ranges.get_part(ins.property);
-            if ins.is_lesser {
-                if r.start < ins.number {
-                    stack.push((
-                        &ins.output,
-                        ranges.replace_part(
-                            ins.property,
-                            Range::new(r.start, min(r.end, ins.number)),
-                        ),
-                    ));
-                }
-                if r.end >= ins.number {
-                    *ranges.get_part_mut(ins.property) = Range::new(max(r.start, ins.number), r.end)
-                } else {
-                    continue 'recur;
-                }
+            let axis = ins.property as usize;
+            let (matched, rest) = if ins.is_lesser {
+                ranges.split_at(axis, ins.number)
             } else {
-                if r.end > ins.number {
-                    stack.push((
-                        &ins.output,
-                        ranges.replace_part(
-                            ins.property,
-                            Range::new(max(r.start, ins.number + 1), r.end),
-                        ),
-                    ));
-                }
-                if r.start <= ins.number {
-                    *ranges.get_part_mut(ins.property) =
-                        Range::new(r.start, min(r.end, ins.number + 1))
-                } else {
-                    continue 'recur;
-                }
+                let (rest, matched) = ranges.split_at(axis, ins.number + 1);
+                (matched, rest)
+            };
+
+            if let Some(matched) = matched {
+                stack.push((&ins.output, matched));
+            }
+            match rest {
+                Some(rest) => ranges = rest,
+                None => continue 'recur,
             }
         }
         stack.push((&work_flow.otherwise, ranges))
@@ -221,12 +165,12 @@ pub fn solve_part_1(file: &str) -> Option<IntType> {
         parts
             .iter()
             .map(|part| {
-                let part_range = Ranges {
-                    x: Range::new(part.x, part.x + 1),
-                    m: Range::new(part.m, part.m + 1),
-                    a: Range::new(part.a, part.a + 1),
-                    s: Range::new(part.s, part.s + 1),
-                };
+                let part_range = Ranges::new([
+                    part.x..part.x + 1,
+                    part.m..part.m + 1,
+                    part.a..part.a + 1,
+                    part.s..part.s + 1,
+                ]);
                 recur_stack(&hashmap, part_range) * part.get_total() as IntType
             })
             .sum(),
@@ -239,12 +183,7 @@ pub fn solve_part_2(file: &str) -> Option<IntType> {
 
     let (hashmap, _) = parse_file(file);
 
-    let ranges = Ranges {
-        x: Range::new(START, END),
-        m: Range::new(START, END),
-        a: Range::new(START, END),
-        s: Range::new(START, END),
-    };
+    let ranges = Ranges::new([START..END, START..END, START..END, START..END]);
 
     Some(recur_stack(&hashmap, ranges))
 }
@@ -278,4 +217,17 @@ mod tests {
         let content = inputs::get_file(DAY, InputType::Input);
         assert_eq!(solve_part_2(&content), Some(125744206494820));
     }
+
+    #[test]
+    fn parses_work_flows() {
+        let mut input = Input::new("px{a<2006:qkq,m>2090:A,rfg}");
+        let work_flow = WorkFlow::parse(&mut input).unwrap();
+        assert_eq!(work_flow.ident, "px");
+        assert_eq!(work_flow.instructions.len(), 2);
+        assert_eq!(work_flow.instructions[1].number, 2090);
+        assert_eq!(work_flow.otherwise, "rfg");
+
+        let error = WorkFlow::parse(&mut Input::new("px{q<1:A,R}")).unwrap_err();
+        assert_eq!(error.column, 6);
+    }
 }
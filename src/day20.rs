@@ -1,10 +1,9 @@
 use itertools::Itertools;
-use once_cell::sync::Lazy;
 use rayon::prelude::*;
-use regex::Regex;
 use std::collections::{HashMap, VecDeque};
 
 use crate::helpers::math::lcm;
+use crate::helpers::parse::parse_lines;
 
 type IntType = u64;
 
@@ -41,35 +40,32 @@ struct Pulse {
 }
 
 fn parse_file(file: &str) -> ModuleContext {
-    const IDENT_RX: &str = r"^([%&]?)(\w+) -> .*$";
-    const DESTINATION_RX: &str = r" (\w+)";
-
-    static RE_IDENT: Lazy<Regex> = Lazy::new(|| Regex::new(IDENT_RX).unwrap());
-    static RE_DEST: Lazy<Regex> = Lazy::new(|| Regex::new(DESTINATION_RX).unwrap());
-
     let mut hashmap = HashMap::new();
 
-    file.lines().for_each(|line| {
-        let (_, [ty, name]) = RE_IDENT.captures(line).unwrap().extract();
-        let destinations: Vec<String> = RE_DEST
-            .captures_iter(line)
-            .map(|c| c.extract())
-            .map(|(_, [dest])| dest.to_string())
-            .collect();
-
+    // %a -> b, c
+    let lines = parse_lines(file, |input| {
+        let module_type = match input.one_of("%&") {
+            Ok('%') => ModuleType::FlipFlop,
+            Ok(_) => ModuleType::Conjunction,
+            Err(_) => ModuleType::None,
+        };
+        let name = input.ident()?;
+        input.literal(" -> ")?;
+        let destinations = input.separated(", ", |input| input.ident())?;
+        Ok((module_type, name, destinations))
+    })
+    .unwrap();
+
+    for (module_type, name, destinations) in lines {
         hashmap.insert(
             name.to_string(),
             Module {
-                module_type: match ty {
-                    "%" => ModuleType::FlipFlop,
-                    "&" => ModuleType::Conjunction,
-                    _ => ModuleType::None,
-                },
+                module_type,
                 callers: Vec::new(),
-                destinations,
+                destinations: destinations.iter().map(|d| d.to_string()).collect(),
             },
         );
-    });
+    }
 
     let key_and_values = hashmap
         .iter()
@@ -3,9 +3,14 @@ type IntType = usize;
 use grid::Grid;
 
 use crate::helpers::{
+    bitgrid::BitGrid,
+    color::Color,
     constructor::{file_to_grid, FromChar},
-    grid_stuff::find_index_of,
-    print_helpers::ToChar,
+    enums_and_types::Position,
+    grid_stuff::{distance_map, find_index_of},
+    math::extrapolate_polynomial,
+    print_helpers::{Canvas, ToChar},
+    trace::{Trace, Tracer},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -35,29 +40,26 @@ impl ToChar for GardenType {
     }
 }
 
-fn spread_out(garden: &Grid<GardenType>, step_grid: Grid<bool>) -> Grid<bool> {
-    let mut new_grid = Grid::new(step_grid.rows(), step_grid.cols());
-    for j in 0..step_grid.rows() {
-        for i in 0..step_grid.cols() {
-            if !step_grid[(j, i)] {
-                continue;
-            }
-            if i > 0 && garden[(j, i - 1)] != GardenType::Rock {
-                new_grid[(j, i - 1)] = true;
-            }
-            if i < step_grid.cols() - 1 && garden[(j, i + 1)] != GardenType::Rock {
-                new_grid[(j, i + 1)] = true;
-            }
-
-            if j > 0 && garden[(j - 1, i)] != GardenType::Rock {
-                new_grid[(j - 1, i)] = true;
-            }
-            if j < step_grid.rows() - 1 && garden[(j + 1, i)] != GardenType::Rock {
-                new_grid[(j + 1, i)] = true;
-            }
-        }
-    }
-    new_grid
+// spreads out once, and shows where the elf can be on top of the garden
+fn step(
+    garden: &Grid<GardenType>,
+    passable: &BitGrid,
+    step_grid: &mut BitGrid,
+    spare: &mut BitGrid,
+    tracer: &mut impl Tracer,
+) {
+    const REACHED: Color = Color {
+        r: 60,
+        g: 200,
+        b: 80,
+    };
+    step_grid.spread_into(passable, spare);
+    std::mem::swap(step_grid, spare);
+    tracer.record(|| {
+        let mut canvas = Canvas::from_grid(garden);
+        canvas.highlight(step_grid.iter_ones(), REACHED);
+        canvas
+    });
 }
 
 fn tile_grid<T: Copy + Default>(base_grid: &Grid<T>, rows: usize, cols: usize) -> Grid<T> {
@@ -70,75 +72,74 @@ fn tile_grid<T: Copy + Default>(base_grid: &Grid<T>, rows: usize, cols: usize) -
     bigger_grid
 }
 
-fn count_true(grid: &Grid<bool>) -> usize {
-    grid.iter().filter(|&&x| x).count()
+// the garden repeated `scale` times each way, and the start in the middle
+fn tile_around_start(garden: &Grid<GardenType>, scale: usize) -> (Grid<GardenType>, Position) {
+    let size = garden.rows();
+    let start_pos = find_index_of(garden, |&x| x == GardenType::Start);
+    let offset = size * (scale / 2);
+    (
+        tile_grid(garden, scale, scale),
+        (start_pos.0 + offset, start_pos.1 + offset),
+    )
+}
+
+// the plots the elf can be on after `steps` steps, from how far away each
+// one is: any plot that's an even number of steps short, as the elf can go
+// back and forth to use the rest up
+fn count_within(distances: &Grid<Option<u32>>, steps: usize) -> IntType {
+    distances
+        .iter()
+        .flatten()
+        .filter(|&&d| d as usize <= steps && (steps - d as usize).is_multiple_of(2))
+        .count()
 }
 
 fn spread_out_and_count(garden: &Grid<GardenType>, steps: usize) -> IntType {
     assert_eq!(garden.rows(), garden.cols());
     let size = garden.rows();
 
-    let start_pos = find_index_of(&garden, |&x| x == GardenType::Start);
-
+    // few enough steps to take them all on a garden they never leave
     let scale = 100;
     if steps < scale * size {
-        let scale = ((steps / size) + 1) * 2 + 1;
-        let bigger_grid = tile_grid(&garden, scale, scale);
-
-        let start_pos = (
-            start_pos.0 + (size * (scale / 2)),
-            start_pos.1 + (size * (scale / 2)),
-        );
-
-        let mut step_grid = Grid::new(bigger_grid.rows(), bigger_grid.cols());
-        step_grid[start_pos] = true;
-
-        for _ in 0..steps {
-            step_grid = spread_out(&bigger_grid, step_grid);
-        }
-        return count_true(&step_grid);
-    }
-
-    let bigger_grid = tile_grid(&garden, 5, 5);
-    let start_pos = (start_pos.0 + (size * 2), start_pos.1 + (size * 2));
-
-    let mut step_grid = Grid::new(bigger_grid.rows(), bigger_grid.cols());
-    step_grid[start_pos] = true;
-
-    if steps < 5 * size {
-        for _ in 0..steps {
-            step_grid = spread_out(&bigger_grid, step_grid);
-        }
-        return count_true(&step_grid);
-    }
-
-    let starting_steps = steps % size;
-    for _ in 0..starting_steps {
-        step_grid = spread_out(&bigger_grid, step_grid);
+        spread_out_and_count_traced(garden, steps, &mut ())
+    } else {
+        extrapolate_count(garden, steps)
     }
-    let r1 = count_true(&step_grid);
-
-    for _ in 0..size {
-        step_grid = spread_out(&bigger_grid, step_grid);
-    }
-    let r2 = count_true(&step_grid);
+}
 
-    for _ in 0..size {
-        step_grid = spread_out(&bigger_grid, step_grid);
-    }
-    let r3 = count_true(&step_grid);
+// the count after a lot of steps, from the first few gardens' worth
+fn extrapolate_count(garden: &Grid<GardenType>, steps: usize) -> IntType {
+    let size = garden.rows();
 
-    // some math I got from the reddit
-    // thanks @aexi
-    let a = (r3 - (2 * r2) + r1) / 2;
-    let b = (4 * r2 - 3 * r1 - r3) / 2;
-    let c = r1 / 1;
+    // a 5x5 garden is big enough for the first 2.5 gardens of steps
+    let (bigger_grid, start_pos) = tile_around_start(garden, 5);
+    let distances = distance_map(&bigger_grid, [start_pos], |&x| x != GardenType::Rock);
+    let r1 = count_within(&distances, steps % size);
+    let r2 = count_within(&distances, steps % size + size);
+    let r3 = count_within(&distances, steps % size + size * 2);
 
+    // the count grows quadratically every `size` steps
     let x = steps / size;
+    extrapolate_polynomial(&[r1, r2, r3], x)
+}
 
-    let ans = (a * x.pow(2)) + (b * x) + c;
+fn spread_out_and_count_traced(
+    garden: &Grid<GardenType>,
+    steps: usize,
+    tracer: &mut impl Tracer,
+) -> IntType {
+    let size = garden.rows();
+    let tiles = ((steps / size) + 1) * 2 + 1;
+    let (bigger_grid, start_pos) = tile_around_start(garden, tiles);
 
-    ans
+    let passable = BitGrid::from_grid(&bigger_grid, |&x| x != GardenType::Rock);
+    let mut step_grid = BitGrid::new(bigger_grid.rows(), bigger_grid.cols());
+    let mut spare = step_grid.clone();
+    step_grid.set(start_pos, true);
+    for _ in 0..steps {
+        step(&bigger_grid, &passable, &mut step_grid, &mut spare, tracer);
+    }
+    step_grid.count_ones()
 }
 
 pub fn solve_part_1(file: &str) -> Option<IntType> {
@@ -161,9 +162,34 @@ pub fn main(file: &str) {
     println!("  part 2: {:?}", solve_part_2(&file));
 }
 
+/// Where the elf can be after each of the first `steps` steps, on the
+/// repeated garden.
+#[allow(unused)]
+pub fn trace(file: &str, steps: usize) -> Trace {
+    let garden: Grid<GardenType> = file_to_grid(file);
+    let mut trace = Trace::new();
+    spread_out_and_count_traced(&garden, steps, &mut trace);
+    trace
+}
+
+/// How many steps it takes to first get to each plot, on the garden
+/// repeated enough times to hold `steps` steps, blue for the nearest.
+#[allow(unused)]
+pub fn render(file: &str, steps: usize) -> Canvas {
+    let garden: Grid<GardenType> = file_to_grid(file);
+    let size = garden.rows();
+    let (bigger_grid, start_pos) = tile_around_start(&garden, ((steps / size) + 1) * 2 + 1);
+
+    let distances = distance_map(&bigger_grid, [start_pos], |&x| x != GardenType::Rock);
+    Canvas::heatmap(&distances, |d| {
+        d.filter(|&d| d as usize <= steps).map(|d| d as f64)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helpers::print_helpers::heat_color;
     use crate::inputs::{self, InputType};
 
     #[test]
@@ -174,7 +200,24 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "to slow / don't work"]
+    fn traces_every_step() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let trace = trace(&content, 6);
+        assert_eq!(trace.frames().len(), 6);
+    }
+
+    #[test]
+    fn renders_reachability() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let canvas = render(&content, 6);
+        let cells = canvas.cells();
+        // the sample is 11 plots across, repeated 3 times with S in the middle
+        assert_eq!(cells.size(), (33, 33));
+        assert_eq!(cells[(16, 16)].bg, Some(heat_color(0.0)));
+        assert_eq!(cells[(0, 0)].bg, None);
+    }
+
+    #[test]
     fn solves_second_problem() {
         let content = inputs::get_file(DAY, InputType::Sample);
         let garden = file_to_grid(&content);
@@ -186,4 +229,36 @@ mod tests {
         assert_eq!(spread_out_and_count(&garden, 1000), 668697);
         // assert_eq!(spread_out_and_count_2(&garden, 5000), 16733044);
     }
+
+    #[test]
+    fn extrapolates_from_distances() {
+        // the sample with a clear path across the middle, so the count grows
+        // the same way every time the elf gets another garden further out
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let mut garden: Grid<GardenType> = file_to_grid(&content);
+        for k in 0..11 {
+            if garden[(5, k)] == GardenType::Rock {
+                garden[(5, k)] = GardenType::Garden;
+            }
+            if garden[(k, 5)] == GardenType::Rock {
+                garden[(k, 5)] = GardenType::Garden;
+            }
+        }
+
+        let (bigger_grid, start_pos) = tile_around_start(&garden, 5);
+        let distances = distance_map(&bigger_grid, [start_pos], |&x| x != GardenType::Rock);
+        for steps in [0, 6, 16, 27] {
+            assert_eq!(
+                count_within(&distances, steps),
+                spread_out_and_count_traced(&garden, steps, &mut ())
+            );
+        }
+
+        for steps in [11 * 4 + 5, 11 * 5 + 2] {
+            assert_eq!(
+                extrapolate_count(&garden, steps),
+                spread_out_and_count_traced(&garden, steps, &mut ())
+            );
+        }
+    }
 }
@@ -1,102 +1,96 @@
-use core::fmt;
-
 use grid::Grid;
-use once_cell::sync::Lazy;
 use rayon::prelude::*;
-use regex::Regex;
 
 use crate::helpers::array_helpers::contains_only;
+use crate::helpers::color::Color;
+use crate::helpers::parse::{parse_lines, Input, ParseError};
+use crate::helpers::print_helpers::Canvas;
+use crate::helpers::space::{Box3, Point3, Vec3, VoxelGrid};
+use crate::helpers::trace::{Trace, Tracer};
 
 type IntType = usize;
 
-#[derive(Debug, Clone, Copy)]
-struct Brick {
-    x1: usize,
-    y1: usize,
-    z1: usize,
-
-    x2: usize,
-    y2: usize,
-    z2: usize,
-}
-impl fmt::Display for Brick {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{},{},{}~{},{},{}",
-            self.x1, self.y1, self.z1, self.x2, self.y2, self.z2
-        )
-    }
+type Brick = Box3<usize>;
+// which brick is in each cell, once they've fallen
+type Space = VoxelGrid<Option<usize>>;
+
+fn point(input: &mut Input) -> Result<Point3<usize>, ParseError> {
+    let x = input.number()?;
+    input.literal(",")?;
+    let y = input.number()?;
+    input.literal(",")?;
+    let z = input.number()?;
+    Ok(Point3::new(x, y, z))
 }
 
-const REGEX: &str = r"(\d+),(\d+),(\d+)~(\d+),(\d+),(\d+)";
+// 1,0,1~1,2,1
 fn parse(file: &str) -> Vec<Brick> {
-    static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-    file.lines()
-        .map(|line| {
-            let (_, [x1, y1, z1, x2, y2, z2]) = RE.captures(line).unwrap().extract();
-            Brick {
-                x1: x1.parse().unwrap(),
-                y1: y1.parse().unwrap(),
-                z1: z1.parse().unwrap(),
-                x2: x2.parse().unwrap(),
-                y2: y2.parse().unwrap(),
-                z2: z2.parse().unwrap(),
-            }
-        })
-        .collect()
+    parse_lines(file, |input| {
+        let a = point(input)?;
+        input.literal("~")?;
+        let b = point(input)?;
+        Ok(Box3::new(a, b))
+    })
+    .unwrap()
 }
 
-fn brick_collisions(
-    layers: &Vec<Grid<Option<usize>>>,
-    brick: Brick,
-    move_down: usize,
-) -> Option<Vec<usize>> {
+// the one cell thick slice of the brick at height `z`
+fn slice_at(brick: Brick, z: usize) -> Brick {
+    Box3 {
+        min: Point3 { z, ..brick.min },
+        max: Point3 { z, ..brick.max },
+    }
+}
+
+fn brick_collisions(layers: &Space, brick: Brick, move_down: usize) -> Option<Vec<usize>> {
     let mut collisions = vec![];
-    for y in brick.y1..=brick.y2 {
-        for x in brick.x1..=brick.x2 {
-            if let Some(n) = layers[brick.z1 - move_down][(y, x)] {
-                if !collisions.contains(&n) {
-                    collisions.push(n);
-                }
-            }
+    for &n in layers
+        .iter_box(slice_at(brick, brick.min.z - move_down))
+        .flatten()
+    {
+        if !collisions.contains(&n) {
+            collisions.push(n);
         }
     }
     (!collisions.is_empty()).then_some(collisions)
 }
 
-fn place_brick(
-    layers: &mut Vec<Grid<Option<usize>>>,
-    brick: Brick,
-    brick_i: usize,
-    move_down: usize,
-) {
-    for z in (brick.z1 - move_down)..=(brick.z2 - move_down) {
-        for y in brick.y1..=brick.y2 {
-            for x in brick.x1..=brick.x2 {
-                layers[z][(y, x)] = Some(brick_i);
-            }
+fn place_brick(layers: &mut Space, brick: Brick, brick_i: usize, move_down: usize) {
+    layers.fill_box(brick - Vec3::new(0, 0, move_down), Some(brick_i));
+}
+
+// looking at the stack from the front, so x across and z going up, with the
+// brick nearest to the front in each spot
+fn side_view(layers: &Space, highlighted: usize) -> Canvas {
+    const FALLEN: Color = Color {
+        r: 230,
+        g: 120,
+        b: 40,
+    };
+    let size = layers.size();
+    let mut view: Grid<Option<usize>> = Grid::new(size.z, size.x);
+    for z in 0..size.z {
+        for x in 0..size.x {
+            view[(size.z - 1 - z, x)] = (0..size.y).find_map(|y| layers[Point3::new(x, y, z)]);
         }
     }
+    let mut canvas = Canvas::from_grid(&view);
+    let fallen = view
+        .indexed_iter()
+        .filter(|(_, &n)| n == Some(highlighted))
+        .map(|(pos, _)| pos);
+    canvas.highlight(fallen, FALLEN);
+    canvas
 }
 
 fn create_and_drop(
     mut bricks: Vec<Brick>,
-) -> (Vec<(usize, Brick, usize)>, Vec<Grid<Option<usize>>>) {
-    assert!(bricks
-        .iter()
-        .all(|b| b.x1 <= b.x2 && b.y1 <= b.y2 && b.z1 <= b.z2));
+    tracer: &mut impl Tracer,
+) -> (Vec<(usize, Brick, usize)>, Space) {
+    let bounds = Box3::bounding(bricks.iter().flat_map(|b| [b.min, b.max])).unwrap();
+    let mut layers = Space::new(bounds.max.x + 1, bounds.max.y + 1, bounds.max.z);
 
-    let max_x = bricks.iter().max_by_key(|b| b.x2).unwrap().x2;
-    let max_y = bricks.iter().max_by_key(|b| b.y2).unwrap().y2;
-    let max_z = bricks.iter().max_by_key(|b| b.z2).unwrap().z2;
-
-    let mut layers: Vec<Grid<Option<usize>>> = vec![];
-    for _ in 0..max_z {
-        layers.push(Grid::new(max_y + 1, max_x + 1))
-    }
-
-    bricks.sort_by_key(|b| b.z1);
+    bricks.sort_by_key(|b| b.min.z);
     let bricks: Vec<(usize, &Brick)> = bricks.iter().enumerate().collect();
 
     // place the pieces
@@ -104,13 +98,14 @@ fn create_and_drop(
         .iter()
         .map(|(i, &brick)| {
             let mut downwards_move = 0;
-            while brick.z1 - downwards_move > 0
+            while brick.min.z - downwards_move > 0
                 && brick_collisions(&layers, brick, downwards_move + 1).is_none()
             {
                 downwards_move += 1;
             }
             // place brick
             place_brick(&mut layers, brick, *i, downwards_move);
+            tracer.record(|| side_view(&layers, *i));
             (*i, brick, downwards_move)
         })
         .collect();
@@ -120,34 +115,30 @@ fn create_and_drop(
 
 pub fn solve_part_1(file: &str) -> Option<IntType> {
     let bricks = parse(file);
-    let (bricks, layers) = create_and_drop(bricks);
+    let (bricks, layers) = create_and_drop(bricks, &mut ());
 
     // count down many you can remove
     Some(
         bricks
             .par_iter()
             .filter(|(i, brick, move_down)| {
-                let layer_above = (brick.z2 - move_down) + 1;
+                let layer_above = (brick.max.z - move_down) + 1;
                 let mut not_supporting = true;
                 let mut no_brick_above = true;
 
                 // loop over the layer, and check if a piece can move down, ignoring i
                 // can just check above self
-                'checking: for y in brick.y1..=brick.y2 {
-                    for x in brick.x1..=brick.x2 {
-                        if let Some(n) = layers[layer_above][(y, x)] {
-                            no_brick_above = false;
+                for &n in layers.iter_box(slice_at(*brick, layer_above)).flatten() {
+                    no_brick_above = false;
 
-                            let (_, checking_brick, move_down) = bricks[n];
+                    let (_, checking_brick, move_down) = bricks[n];
 
-                            let collisions =
-                                brick_collisions(&layers, checking_brick, move_down + 1).unwrap();
-                            if contains_only(&collisions, &[*i]) {
-                                // there is another object supporting it
-                                not_supporting = false;
-                                break 'checking;
-                            }
-                        }
+                    let collisions =
+                        brick_collisions(&layers, checking_brick, move_down + 1).unwrap();
+                    if contains_only(&collisions, &[*i]) {
+                        // there is another object supporting it
+                        not_supporting = false;
+                        break;
                     }
                 }
                 not_supporting || no_brick_above
@@ -158,7 +149,7 @@ pub fn solve_part_1(file: &str) -> Option<IntType> {
 
 pub fn solve_part_2(file: &str) -> Option<IntType> {
     let bricks = parse(file);
-    let (bricks, layers) = create_and_drop(bricks);
+    let (bricks, layers) = create_and_drop(bricks, &mut ());
     // when checking the above bricks, if it isn't supported, recur check
     Some(
         bricks
@@ -166,34 +157,25 @@ pub fn solve_part_2(file: &str) -> Option<IntType> {
             .map(|(i, brick, move_down)| {
                 let mut total_chain = 0;
 
-                let mut layer_checking = brick.z2 - move_down + 1;
+                let mut layer_checking = brick.max.z - move_down + 1;
                 let mut disappeared_bricks = vec![*i];
-                while layer_checking < layers.len() && !disappeared_bricks.is_empty() {
+                while layer_checking < layers.size().z && !disappeared_bricks.is_empty() {
                     let mut new_disappeared = vec![];
                     for b_i in disappeared_bricks.iter() {
                         let (_, b, _) = bricks[*b_i];
-                        for y in b.y1..=b.y2 {
-                            for x in b.x1..=b.x2 {
-                                if let Some(n) = layers[layer_checking][(y, x)] {
-                                    if new_disappeared.contains(&n)
-                                        || disappeared_bricks.contains(&n)
-                                    {
-                                        continue;
-                                    }
-                                    let (_, checking_brick, checking_move_down) = bricks[n];
-                                    let collisions = brick_collisions(
-                                        &layers,
-                                        checking_brick,
-                                        checking_move_down + 1,
-                                    )
+                        for &n in layers.iter_box(slice_at(b, layer_checking)).flatten() {
+                            if new_disappeared.contains(&n) || disappeared_bricks.contains(&n) {
+                                continue;
+                            }
+                            let (_, checking_brick, checking_move_down) = bricks[n];
+                            let collisions =
+                                brick_collisions(&layers, checking_brick, checking_move_down + 1)
                                     .unwrap();
-                                    // contains only
-                                    if contains_only(&collisions, &disappeared_bricks) {
-                                        // new brick to drop
-                                        new_disappeared.push(n);
-                                        total_chain += 1;
-                                    }
-                                }
+                            // contains only
+                            if contains_only(&collisions, &disappeared_bricks) {
+                                // new brick to drop
+                                new_disappeared.push(n);
+                                total_chain += 1;
                             }
                         }
                     }
@@ -201,7 +183,7 @@ pub fn solve_part_2(file: &str) -> Option<IntType> {
                     // filter those who wont matter
                     disappeared_bricks.retain(|b_i| {
                         let (_i, b, m_d) = bricks[*b_i];
-                        layer_checking - 1 <= b.z2 - m_d
+                        layer_checking - 1 <= b.max.z - m_d
                     });
 
                     disappeared_bricks.append(&mut new_disappeared);
@@ -222,6 +204,14 @@ pub fn main(file: &str) {
     println!("  part 2: {:?}", solve_part_2(&file));
 }
 
+/// The stack from the front, after each brick has fallen into place.
+#[allow(unused)]
+pub fn trace(file: &str) -> Trace {
+    let mut trace = Trace::new();
+    create_and_drop(parse(file), &mut trace);
+    trace
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,4 +228,11 @@ mod tests {
         let content = inputs::get_file(DAY, InputType::Sample);
         assert_eq!(solve_part_2(&content), Some(7))
     }
+
+    #[test]
+    fn traces_every_brick() {
+        let content = inputs::get_file(DAY, InputType::Sample);
+        let trace = trace(&content);
+        assert_eq!(trace.frames().len(), 7);
+    }
 }
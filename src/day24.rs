@@ -1,88 +1,57 @@
 use std::fmt::Debug;
-use std::str::FromStr;
-use z3::ast::{Ast, Int};
-use z3::*;
 
 use crate::helpers::constructor::line_to_digits;
+use crate::helpers::numeric::{solve_linear_system, BigInt, Rational};
+use crate::helpers::scan::ScanInt;
+use crate::helpers::space::{Point3, Vec3};
 
 type IntType = i64;
-type HailType = f64;
+type HailType = i128;
 
 #[derive(Debug, Clone, Copy)]
 struct Hail<T: Copy + Clone + Debug + PartialEq> {
-    px: T,
-    py: T,
-    pz: T,
-    vx: T,
-    vy: T,
-    vz: T,
+    p: Point3<T>,
+    v: Vec3<T>,
 }
 
-const ZERO: HailType = 0.0;
-
 impl Hail<HailType> {
-    fn to_pos(&self) -> (HailType, HailType, HailType) {
-        (self.px, self.py, self.pz)
-    }
-    fn next_hail(&self) -> (HailType, HailType, HailType) {
-        (self.px + self.vx, self.py + self.vy, self.pz + self.vz)
-    }
-
-    fn is_in_future(&self, x: HailType, y: HailType) -> bool {
-        !((self.vx > ZERO && self.px > x)
-            || (self.vx < ZERO && self.px < x)
-            || (self.vy > ZERO && self.py > y)
-            || (self.vy < ZERO && self.py < y))
-    }
-
     fn intersection_between(&self, other: &Self, min: HailType, max: HailType) -> bool {
-        let (x1, y1, ..) = self.to_pos();
-        let (x2, y2, ..) = self.next_hail();
-        let (x3, y3, ..) = other.to_pos();
-        let (x4, y4, ..) = other.next_hail();
-
-        let m1 = (y2 - y1) / (x2 - x1);
-        let m2 = (y4 - y3) / (x4 - x3);
-
-        let b1 = y1 - m1 * x1;
-        let b2 = y3 - m2 * x3;
-
-        // let (m1_p1, m1_p2) = (y2 - y1, x2 - x1);
-        // let (m2_p1, m2_p2) = (y4 - y3, x4 - x3);
-
-        // let (b1_p1, b1_p2) = ((y1 * m1_p2) - (m1_p1 * x1), m1_p2);
-        // let (b2_p1, b2_p2) = ((y3 * m2_p2) - (m2_p1 * x3), m2_p2);
-
-        if m1 == m2 {
-            // if m1_p1 * m2_p2 == m2_p1 * m1_p2 {
+        // solve self.p + t * self.v = other.p + s * other.v, for x and y
+        let (p, v) = (self.p, self.v);
+        let (other_p, other_v) = (other.p, other.v);
+        let det = other_v.x * v.y - v.x * other_v.y;
+        if det == 0 {
             // parallel
             return false;
         }
 
-        let x = (b2 - b1) / (m1 - m2);
-        let y = (m1 * x) + b1;
+        let d = other_p - p;
+        let t = Rational::new(other_v.x * d.y - other_v.y * d.x, det);
+        let s = Rational::new(v.x * d.y - v.y * d.x, det);
 
-        if !self.is_in_future(x, y) || !other.is_in_future(x, y) {
-            false
-        } else {
-            // check intersections
-            x >= min && x <= max && y >= min && y <= max
+        let zero = Rational::from(0);
+        if t < zero || s < zero {
+            // crossed in the past
+            return false;
         }
+
+        let x = Rational::from(p.x) + t * Rational::from(v.x);
+        let y = Rational::from(p.y) + t * Rational::from(v.y);
+
+        // check intersections
+        let (min, max) = (Rational::from(min), Rational::from(max));
+        x >= min && x <= max && y >= min && y <= max
     }
 }
 
-fn parse<T: Copy + Clone + Debug + PartialEq + FromStr + Default>(file: &str) -> Vec<Hail<T>> {
+fn parse<T: Debug + PartialEq + ScanInt>(file: &str) -> Vec<Hail<T>> {
     file.lines()
         .map(|line| {
             let p = line_to_digits(line);
             assert_eq!(p.len(), 6);
             Hail {
-                px: p[0],
-                py: p[1],
-                pz: p[2],
-                vx: p[3],
-                vy: p[4],
-                vz: p[5],
+                p: Point3::new(p[0], p[1], p[2]),
+                v: Vec3::new(p[3], p[4], p[5]),
             }
         })
         .collect()
@@ -110,46 +79,44 @@ fn count_intersections_2d(
 
 pub fn solve_part_1(file: &str) -> Option<IntType> {
     let hail_stones = parse(file);
-    const MIN: HailType = 200000000000000.0;
-    const MAX: HailType = 400000000000000.0;
+    const MIN: HailType = 200000000000000;
+    const MAX: HailType = 400000000000000;
     Some(count_intersections_2d(&hail_stones, MIN, MAX))
 }
 
 pub fn solve_part_2(file: &str) -> Option<IntType> {
-    let hail_stones = parse(file);
-
-    let cfg = Config::new();
-    let ctx = Context::new(&cfg);
-    let solver = Solver::new(&ctx);
-
-    let px = Int::new_const(&ctx, "px");
-    let py = Int::new_const(&ctx, "py");
-    let pz = Int::new_const(&ctx, "pz");
-    let vx = Int::new_const(&ctx, "vx");
-    let vy = Int::new_const(&ctx, "vy");
-    let vz = Int::new_const(&ctx, "vz");
-
-    for hail in hail_stones {
-        let pxn = Int::from_i64(&ctx, hail.px);
-        let pyn = Int::from_i64(&ctx, hail.py);
-        let pzn = Int::from_i64(&ctx, hail.pz);
-        let vxn = Int::from_i64(&ctx, hail.vx);
-        let vyn = Int::from_i64(&ctx, hail.vy);
-        let vzn = Int::from_i64(&ctx, hail.vz);
-        let tn = Int::fresh_const(&ctx, "t");
-
-        solver.assert(&(&pxn + &vxn * &tn)._eq(&(&px + &vx * &tn)));
-        solver.assert(&(&pyn + &vyn * &tn)._eq(&(&py + &vy * &tn)));
-        solver.assert(&(&pzn + &vzn * &tn)._eq(&(&pz + &vz * &tn)));
+    let hail_stones: Vec<Hail<IntType>> = parse(file);
+
+    // the rock and a hailstone collide when (P - p) x (V - v) = 0, taking
+    // two of those equations away from each other cancels out the P x V
+    // term, leaving equations that are linear in P and V
+    let r = |n: IntType| Rational::from(BigInt::from(n));
+    let coords = |h: &Hail<IntType>| (h.p.to_array(), h.v.to_array());
+    let (p0, v0) = coords(&hail_stones[0]);
+
+    let mut rows = Vec::new();
+    for other in hail_stones[1..3].iter() {
+        let (p1, v1) = coords(other);
+        for (a, b) in [(0, 1), (0, 2), (1, 2)] {
+            // unknowns are [px, py, pz, vx, vy, vz]
+            let mut row = vec![r(0); 7];
+            row[a] = r(v1[b] - v0[b]);
+            row[b] = r(v0[a] - v1[a]);
+            row[3 + a] = r(p0[b] - p1[b]);
+            row[3 + b] = r(p1[a] - p0[a]);
+            row[6] = r(p1[a]) * r(v1[b]) - r(p1[b]) * r(v1[a]) - r(p0[a]) * r(v0[b])
+                + r(p0[b]) * r(v0[a]);
+            rows.push(row);
+        }
     }
 
-    solver.check();
-    let model = solver.get_model().unwrap();
-    let x = model.get_const_interp(&px).unwrap().as_i64().unwrap();
-    let y = model.get_const_interp(&py).unwrap().as_i64().unwrap();
-    let z = model.get_const_interp(&pz).unwrap().as_i64().unwrap();
-
-    Some(x + y + z)
+    let rock = solve_linear_system(rows)?;
+    // no rock at whole number coordinates gives a fraction here
+    let mut total = BigInt::from(0i64);
+    for c in &rock[..3] {
+        total = total + c.to_integer()?;
+    }
+    total.to_i128().map(|total| total as IntType)
 }
 
 const DAY: u8 = 24;
@@ -170,12 +137,16 @@ mod tests {
     fn solves_first_problem() {
         let content = inputs::get_file(DAY, InputType::Sample);
         let hail_stones = parse(&content);
-        assert_eq!(count_intersections_2d(&hail_stones, 7.0, 27.0), 2)
+        assert_eq!(count_intersections_2d(&hail_stones, 7, 27), 2)
     }
 
     #[test]
     fn solves_second_problem() {
         let content = inputs::get_file(DAY, InputType::Sample);
-        assert_eq!(solve_part_2(&content), Some(47))
+        assert_eq!(solve_part_2(&content), Some(47));
+
+        // the only rock that hits these starts at x = 1/2
+        let halfway = "-1, 2, 1 @ 2, -1, 0\n5, -3, -6 @ -1, 2, 1\n3, -10, 13 @ 0, 3, -4";
+        assert_eq!(solve_part_2(halfway), None);
     }
 }
@@ -0,0 +1,240 @@
+use std::collections::VecDeque;
+
+/// Where one of the patterns was found, as byte offsets into the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// the index of the pattern in the list it was built from
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+// a trie of the patterns, with the failure links already followed so every
+// byte is a single table lookup
+#[derive(Debug, Clone)]
+struct Automaton {
+    next: Vec<[u32; 256]>,
+    // the patterns that end at each state, in the order they were given
+    found: Vec<Vec<usize>>,
+}
+
+impl Automaton {
+    fn new<'a, I: Iterator<Item = &'a [u8]>>(patterns: I, ignore_case: bool) -> Self {
+        // state 0 is the root, and 0 is also "no edge yet" while building
+        let mut next = vec![[0u32; 256]];
+        let mut found = vec![vec![]];
+        for (k, pattern) in patterns.enumerate() {
+            let mut state = 0;
+            for b in pattern {
+                let b = if ignore_case {
+                    b.to_ascii_lowercase()
+                } else {
+                    *b
+                };
+                if next[state][b as usize] == 0 {
+                    next[state][b as usize] = next.len() as u32;
+                    next.push([0; 256]);
+                    found.push(vec![]);
+                }
+                state = next[state][b as usize] as usize;
+            }
+            found[state].push(k);
+        }
+
+        // breadth first, so each state's failure state is finished before it
+        let mut fail = vec![0; next.len()];
+        let mut queue: VecDeque<usize> = next[0]
+            .iter()
+            .filter(|&&s| s != 0)
+            .map(|&s| s as usize)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            let inherited = found[fail[state]].clone();
+            found[state].extend(inherited);
+            let fallbacks = next[fail[state]];
+            for (child, fallback) in next[state].iter_mut().zip(fallbacks) {
+                if *child == 0 {
+                    *child = fallback;
+                } else {
+                    fail[*child as usize] = fallback as usize;
+                    queue.push_back(*child as usize);
+                }
+            }
+        }
+        found.iter_mut().for_each(|f| f.sort_unstable());
+        if ignore_case {
+            for row in next.iter_mut() {
+                for b in b'A'..=b'Z' {
+                    row[b as usize] = row[b.to_ascii_lowercase() as usize];
+                }
+            }
+        }
+
+        Automaton { next, found }
+    }
+
+    // (bytes read so far, pattern) for every pattern found along the way
+    fn scan<'a, I>(&'a self, bytes: I) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        I: Iterator<Item = u8> + 'a,
+    {
+        bytes
+            .scan(0, move |state, b| {
+                *state = self.next[*state][b as usize] as usize;
+                Some(&self.found[*state])
+            })
+            .enumerate()
+            .flat_map(|(k, found)| found.iter().map(move |&pattern| (k + 1, pattern)))
+    }
+}
+
+/// Finds any of a list of patterns in a text, reading each byte once.
+///
+/// Works on bytes, but a whole UTF-8 pattern can only match on char
+/// boundaries of UTF-8 text, so slicing the text at a match is safe.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    forward: Automaton,
+    // the same patterns reversed, for searching from the end
+    backward: Automaton,
+    lengths: Vec<usize>,
+    longest: usize,
+}
+
+impl AhoCorasick {
+    /// Panics if a pattern is empty.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        AhoCorasick::build(patterns, false)
+    }
+
+    /// Like `new`, but `A` to `Z` match `a` to `z`. Other letters still have
+    /// to match exactly.
+    pub fn ignoring_ascii_case<S: AsRef<str>>(patterns: &[S]) -> Self {
+        AhoCorasick::build(patterns, true)
+    }
+
+    fn build<S: AsRef<str>>(patterns: &[S], ignore_case: bool) -> Self {
+        let patterns: Vec<&[u8]> = patterns.iter().map(|p| p.as_ref().as_bytes()).collect();
+        assert!(
+            patterns.iter().all(|p| !p.is_empty()),
+            "can't search for an empty pattern"
+        );
+        let reversed: Vec<Vec<u8>> = patterns
+            .iter()
+            .map(|p| p.iter().rev().copied().collect())
+            .collect();
+
+        AhoCorasick {
+            forward: Automaton::new(patterns.iter().copied(), ignore_case),
+            backward: Automaton::new(reversed.iter().map(|p| p.as_slice()), ignore_case),
+            lengths: patterns.iter().map(|p| p.len()).collect(),
+            longest: patterns.iter().map(|p| p.len()).max().unwrap_or(0),
+        }
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Every match, including ones that overlap, in the order they end.
+    pub fn find_overlapping<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Match> + 'a {
+        self.forward.scan(text.bytes()).map(|(end, pattern)| Match {
+            pattern,
+            start: end - self.lengths[pattern],
+            end,
+        })
+    }
+
+    /// The match that starts first, the earlier pattern if two start at the
+    /// same place. Only reads as far as a match that starts first could end.
+    pub fn first(&self, text: &str) -> Option<Match> {
+        let mut best: Option<Match> = None;
+        for found in self.find_overlapping(text) {
+            if let Some(best) = best {
+                if found.end > best.start + self.longest {
+                    break;
+                }
+            }
+            if best.is_none_or(|best| (found.start, found.pattern) < (best.start, best.pattern)) {
+                best = Some(found);
+            }
+        }
+        best
+    }
+
+    /// The match that starts last, the earlier pattern if two start at the
+    /// same place. Reads from the end, stopping at the first match.
+    pub fn last(&self, text: &str) -> Option<Match> {
+        let mut found = self.backward.scan(text.bytes().rev());
+        let (read, pattern) = found.next()?;
+        // everything else found reading that byte starts at the same place
+        let pattern = found
+            .take_while(|&(other_read, _)| other_read == read)
+            .map(|(_, other)| other)
+            .fold(pattern, usize::min);
+        let start = text.len() - read;
+        Some(Match {
+            pattern,
+            start,
+            end: start + self.lengths[pattern],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_overlapping_matches() {
+        let matcher = AhoCorasick::new(&["he", "she", "his", "hers"]);
+        let found: Vec<(usize, usize)> = matcher
+            .find_overlapping("ushers")
+            .map(|m| (m.pattern, m.start))
+            .collect();
+        assert_eq!(found, vec![(0, 2), (1, 1), (3, 2)]);
+        assert_eq!(matcher.find_overlapping("").count(), 0);
+    }
+
+    #[test]
+    fn finds_first_and_last() {
+        // the long pattern starts first but ends last
+        let matcher = AhoCorasick::new(&["abcd", "bc", "d"]);
+        let first = matcher.first("xabcdx").unwrap();
+        assert_eq!((first.pattern, first.start, first.end), (0, 1, 5));
+        let last = matcher.last("xabcdx").unwrap();
+        assert_eq!((last.pattern, last.start, last.end), (2, 4, 5));
+        assert_eq!(matcher.first("xyz"), None);
+        assert_eq!(matcher.last("xyz"), None);
+
+        // ties go to the earlier pattern
+        let matcher = AhoCorasick::new(&["ab", "a"]);
+        assert_eq!(matcher.last("aab").unwrap().pattern, 0);
+        assert_eq!(matcher.first("aab").unwrap().start, 0);
+    }
+
+    #[test]
+    fn matches_utf8() {
+        let matcher = AhoCorasick::new(&["é", "zéro", "三"]);
+        let text = "x三zéroé";
+        let first = matcher.first(text).unwrap();
+        assert_eq!(&text[first.start..first.end], "三");
+        let last = matcher.last(text).unwrap();
+        assert_eq!(&text[last.start..last.end], "é");
+        assert_eq!(last.end, text.len());
+    }
+
+    #[test]
+    fn ignores_ascii_case() {
+        let matcher = AhoCorasick::ignoring_ascii_case(&["One", "tWO"]);
+        let found: Vec<usize> = matcher
+            .find_overlapping("ONEtwoone")
+            .map(|m| m.start)
+            .collect();
+        assert_eq!(found, vec![0, 3, 6]);
+        assert_eq!(
+            AhoCorasick::new(&["One"]).find_overlapping("ONE").count(),
+            0
+        );
+    }
+}
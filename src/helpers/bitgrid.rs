@@ -0,0 +1,404 @@
+use std::ops::Range;
+
+use grid::Grid;
+
+use super::enums_and_types::Position;
+
+const WORD: usize = u64::BITS as usize;
+
+/// A grid of bools packed 64 to a word, so whole rows can be shifted and
+/// combined a word at a time.
+///
+/// Column `i` of a row is bit `i % 64` of word `i / 64`. Bits past the last
+/// column are always zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(WORD);
+        BitGrid {
+            rows,
+            cols,
+            words_per_row,
+            words: vec![0; rows * words_per_row],
+        }
+    }
+
+    /// true where `f` is true for the cell of `grid`
+    pub fn from_grid<T, F>(grid: &Grid<T>, f: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut bits = BitGrid::new(grid.rows(), grid.cols());
+        for (pos, item) in grid.indexed_iter() {
+            if f(item) {
+                bits.set(pos, true);
+            }
+        }
+        bits
+    }
+
+    pub fn to_grid(&self) -> Grid<bool> {
+        let mut grid = Grid::new(self.rows, self.cols);
+        for pos in self.iter_ones() {
+            grid[pos] = true;
+        }
+        grid
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn row(&self, j: usize) -> &[u64] {
+        &self.words[j * self.words_per_row..(j + 1) * self.words_per_row]
+    }
+
+    // clears the unused bits at the end of every row
+    fn mask_last_words(&mut self) {
+        let used = self.cols % WORD;
+        if used == 0 {
+            return;
+        }
+        let mask = (1 << used) - 1;
+        for j in 0..self.rows {
+            self.words[(j + 1) * self.words_per_row - 1] &= mask;
+        }
+    }
+
+    /// `None` if the position is outside the grid
+    pub fn get(&self, (j, i): Position) -> Option<bool> {
+        if j >= self.rows || i >= self.cols {
+            return None;
+        }
+        let word = self.words[j * self.words_per_row + i / WORD];
+        Some(word >> (i % WORD) & 1 == 1)
+    }
+
+    /// Panics if the position is outside the grid.
+    pub fn set(&mut self, (j, i): Position, value: bool) {
+        assert!(j < self.rows && i < self.cols, "position outside the grid");
+        let word = &mut self.words[j * self.words_per_row + i / WORD];
+        if value {
+            *word |= 1 << (i % WORD);
+        } else {
+            *word &= !(1 << (i % WORD));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// every position that is set, row by row
+    pub fn iter_ones(&self) -> impl Iterator<Item = Position> + '_ {
+        self.words.iter().enumerate().flat_map(move |(k, &word)| {
+            let (j, first_col) = (k / self.words_per_row, (k % self.words_per_row) * WORD);
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some((j, first_col + bit))
+            })
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn or_assign(&mut self, other: &BitGrid) {
+        assert_eq!(self.size(), other.size());
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a |= b);
+    }
+
+    pub fn and_assign(&mut self, other: &BitGrid) {
+        assert_eq!(self.size(), other.size());
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a &= b);
+    }
+
+    /// clears every bit that is set in `other`
+    pub fn and_not_assign(&mut self, other: &BitGrid) {
+        assert_eq!(self.size(), other.size());
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a &= !b);
+    }
+
+    /// flips every bit
+    pub fn invert(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = !*w);
+        self.mask_last_words();
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Writes the cells next to (but not on) a set cell, that are also set in
+    /// `passable`, into `out`. Nothing is allocated, so two grids can be
+    /// swapped back and forth every step.
+    pub fn spread_into(&self, passable: &BitGrid, out: &mut BitGrid) {
+        assert_eq!(self.size(), passable.size());
+        assert_eq!(self.size(), out.size());
+        self.spread_rows(passable, out, 0..self.rows);
+        out.mask_last_words();
+    }
+
+    // `spread_into`, only writing the rows in `rows`
+    fn spread_rows(&self, passable: &BitGrid, out: &mut BitGrid, rows: Range<usize>) {
+        let n = self.words_per_row;
+        for j in rows {
+            let row = self.row(j);
+            for k in 0..n {
+                // bit i moves to i + 1 and i - 1, carrying between words
+                let right = (row[k] << 1) | if k > 0 { row[k - 1] >> (WORD - 1) } else { 0 };
+                let left = (row[k] >> 1)
+                    | if k + 1 < n {
+                        row[k + 1] << (WORD - 1)
+                    } else {
+                        0
+                    };
+                let up = if j + 1 < self.rows {
+                    self.words[(j + 1) * n + k]
+                } else {
+                    0
+                };
+                let down = if j > 0 {
+                    self.words[(j - 1) * n + k]
+                } else {
+                    0
+                };
+
+                out.words[j * n + k] = (left | right | up | down) & passable.words[j * n + k];
+            }
+        }
+    }
+
+    /// `spread_into` a new grid.
+    pub fn spread(&self, passable: &BitGrid) -> BitGrid {
+        let mut out = BitGrid::new(self.rows, self.cols);
+        self.spread_into(passable, &mut out);
+        out
+    }
+
+    /// Every cell of `passable` that can be reached from `start` by going up,
+    /// down, left and right through `passable` cells.
+    ///
+    /// Spreads a word at a time, but only from the cells reached in the last
+    /// round, and only over the rows they are in and the ones either side, so
+    /// a long winding path doesn't go over the whole grid every step.
+    pub fn flood_fill(passable: &BitGrid, start: Position) -> BitGrid {
+        let mut filled = BitGrid::new(passable.rows, passable.cols);
+        if passable.get(start) != Some(true) {
+            return filled;
+        }
+        filled.set(start, true);
+
+        let n = passable.words_per_row;
+        let mut frontier = filled.clone();
+        let mut next = BitGrid::new(passable.rows, passable.cols);
+        // the rows the frontier is in
+        let mut rows = start.0..start.0 + 1;
+        while !rows.is_empty() {
+            let around = rows.start.saturating_sub(1)..(rows.end + 1).min(passable.rows);
+            frontier.spread_rows(passable, &mut next, around.clone());
+
+            let mut reached: Option<Range<usize>> = None;
+            for j in around {
+                for w in j * n..(j + 1) * n {
+                    next.words[w] &= !filled.words[w];
+                    filled.words[w] |= next.words[w];
+                    if next.words[w] != 0 {
+                        reached = Some(reached.map_or(j..j + 1, |r| r.start..j + 1));
+                    }
+                }
+            }
+
+            frontier.words[rows.start * n..rows.end * n].fill(0);
+            std::mem::swap(&mut frontier, &mut next);
+            rows = reached.unwrap_or(0..0);
+        }
+        filled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_strs(lines: &[&str]) -> BitGrid {
+        let rows: Vec<bool> = lines.concat().chars().map(|c| c == '#').collect();
+        BitGrid::from_grid(&Grid::from_vec(rows, lines[0].len()), |&x| x)
+    }
+
+    #[test]
+    fn sets_bits_across_words() {
+        let mut bits = BitGrid::new(3, 130);
+        bits.set((0, 0), true);
+        bits.set((1, 63), true);
+        bits.set((1, 64), true);
+        bits.set((2, 129), true);
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(bits.get((1, 64)), Some(true));
+        assert_eq!(bits.get((1, 65)), Some(false));
+        assert_eq!(bits.get((3, 0)), None);
+        assert_eq!(
+            bits.iter_ones().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 63), (1, 64), (2, 129)]
+        );
+
+        bits.invert();
+        assert_eq!(bits.count_ones(), 3 * 130 - 4);
+        assert_eq!(bits.to_grid().iter().filter(|&&x| x).count(), 3 * 130 - 4);
+    }
+
+    #[test]
+    fn spreads_to_neighbours() {
+        // spreading across the boundary between two words
+        let mut reached = BitGrid::new(3, 100);
+        reached.set((1, 63), true);
+        let mut passable = BitGrid::new(3, 100);
+        passable.invert();
+        passable.set((0, 63), false);
+
+        let spread = reached.spread(&passable);
+        assert_eq!(
+            spread.iter_ones().collect::<Vec<_>>(),
+            vec![(1, 62), (1, 64), (2, 63)]
+        );
+
+        // nothing wraps around the edges
+        let mut corner = BitGrid::new(2, 64);
+        corner.set((0, 63), true);
+        passable = BitGrid::new(2, 64);
+        passable.invert();
+        assert_eq!(
+            corner.spread(&passable).iter_ones().collect::<Vec<_>>(),
+            vec![(0, 62), (1, 63)]
+        );
+    }
+
+    #[test]
+    fn floods_passable_cells() {
+        let walls = from_strs(&[
+            "....#...", //
+            ".##.#.#.", ".#.##.#.", ".####.#.", "......#.",
+        ]);
+        let mut passable = walls.clone();
+        passable.invert();
+
+        let filled = BitGrid::flood_fill(&passable, (0, 0));
+        // everything but the 15 walls and the closed off (2, 2)
+        assert_eq!(filled.count_ones(), 40 - 15 - 1);
+        assert_eq!(filled.get((2, 2)), Some(false));
+        assert_eq!(filled.get((4, 7)), Some(true));
+        assert_eq!(BitGrid::flood_fill(&passable, (0, 4)).count_ones(), 0);
+    }
+
+    #[test]
+    fn floods_winding_paths() {
+        // a path that goes back and forth across 100 columns, down 40 rows
+        let (rows, cols) = (40, 100);
+        let mut passable = BitGrid::new(rows, cols);
+        for j in 0..rows {
+            for i in 0..cols {
+                let gap = if j % 4 == 1 { cols - 1 } else { 0 };
+                passable.set((j, i), j % 2 == 0 || i == gap);
+            }
+        }
+        let filled = BitGrid::flood_fill(&passable, (0, 0));
+        assert_eq!(filled, passable);
+
+        passable.set((20, 50), false);
+        let filled = BitGrid::flood_fill(&passable, (0, 0));
+        assert_eq!(filled.count_ones(), 10 * cols + 10 + 50);
+        assert_eq!(filled.get((39, 0)), Some(false));
+    }
+
+    // cargo test --release -- --ignored --nocapture compares_with_byte_grids
+    #[test]
+    #[ignore = "benchmark"]
+    fn compares_with_byte_grids() {
+        use std::time::Instant;
+
+        // about the size of day 21 part 2, with a rock every few cells
+        let (rows, cols, steps) = (655, 655, 327);
+        let rock = |j: usize, i: usize| (j * 7 + i * 13).is_multiple_of(11);
+        let start = (rows / 2, cols / 2 + 1);
+
+        let start_time = Instant::now();
+        let mut bytes: Grid<bool> = Grid::new(rows, cols);
+        bytes[start] = true;
+        for _ in 0..steps {
+            let mut next: Grid<bool> = Grid::new(rows, cols);
+            for j in 0..rows {
+                for i in 0..cols {
+                    if !bytes[(j, i)] {
+                        continue;
+                    }
+                    for (y, x) in [
+                        (j + 1, i),
+                        (j.wrapping_sub(1), i),
+                        (j, i + 1),
+                        (j, i.wrapping_sub(1)),
+                    ] {
+                        if y < rows && x < cols && !rock(y, x) {
+                            next[(y, x)] = true;
+                        }
+                    }
+                }
+            }
+            bytes = next;
+        }
+        let byte_time = start_time.elapsed();
+
+        let start_time = Instant::now();
+        let mut passable = BitGrid::new(rows, cols);
+        for j in 0..rows {
+            for i in 0..cols {
+                passable.set((j, i), !rock(j, i));
+            }
+        }
+        let mut bits = BitGrid::new(rows, cols);
+        let mut spare = BitGrid::new(rows, cols);
+        bits.set(start, true);
+        for _ in 0..steps {
+            bits.spread_into(&passable, &mut spare);
+            std::mem::swap(&mut bits, &mut spare);
+        }
+        let bit_time = start_time.elapsed();
+
+        assert_eq!(bits.count_ones(), bytes.iter().filter(|&&x| x).count());
+        println!("Grid<bool>: {:?}", byte_time);
+        println!("BitGrid:    {:?}", bit_time);
+        println!(
+            "speedup:    {:.1}x",
+            byte_time.as_secs_f64() / bit_time.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorError {
+    /// a character that isn't `0-9`, `a-f` or `A-F`
+    InvalidDigit(char),
+    /// how many hex digits there were, a colour needs 3 or 6
+    WrongLength(usize),
+    /// the number doesn't fit
+    Overflow,
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorError::InvalidDigit(c) => write!(f, "{c:?} is not a hex digit"),
+            ColorError::WrongLength(n) => write!(f, "expected 3 or 6 hex digits, got {n}"),
+            ColorError::Overflow => write!(f, "hex number is too big"),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+/// The value of a hex digit, upper or lower case.
+pub fn to_hex(char: char) -> Result<u8, ColorError> {
+    char.to_digit(16)
+        .map(|d| d as u8)
+        .ok_or(ColorError::InvalidDigit(char))
+}
+
+pub fn hex_to_bin(hex: &str) -> Result<usize, ColorError> {
+    if hex.is_empty() {
+        return Err(ColorError::WrongLength(0));
+    }
+    let mut value: usize = 0;
+    for u in hex.chars() {
+        let digit = to_hex(u)? as usize;
+        value = value
+            .checked_mul(16)
+            .and_then(|z| z.checked_add(digit))
+            .ok_or(ColorError::Overflow)?;
+    }
+    Ok(value)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+impl Color {
+    /// Parses `rrggbb` or the short `rgb`, with or without a leading `#`.
+    pub fn from_string(str: &str) -> Result<Self, ColorError> {
+        let hex = str.strip_prefix('#').unwrap_or(str);
+        let digits = hex.chars().map(to_hex).collect::<Result<Vec<u8>, _>>()?;
+
+        match digits[..] {
+            [r1, r2, g1, g2, b1, b2] => Ok(Color {
+                r: (r1 << 4) | r2,
+                g: (g1 << 4) | g2,
+                b: (b1 << 4) | b2,
+            }),
+            // #abc is #aabbcc
+            [r, g, b] => Ok(Color {
+                r: r * 17,
+                g: g * 17,
+                b: b * 17,
+            }),
+            _ => Err(ColorError::WrongLength(digits.len())),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::from_string(s)
+    }
+}
+
+/// Formats as `#rrggbb`, so it parses back to the same colour.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(to_hex('7'), Ok(7));
+        assert_eq!(to_hex('b'), Ok(11));
+        assert_eq!(to_hex('F'), Ok(15));
+        assert_eq!(to_hex('g'), Err(ColorError::InvalidDigit('g')));
+
+        assert_eq!(hex_to_bin("70c71"), Ok(461937));
+        assert_eq!(hex_to_bin("1B58A2"), Ok(0x1b58a2));
+        assert_eq!(hex_to_bin(""), Err(ColorError::WrongLength(0)));
+        assert_eq!(hex_to_bin("12x"), Err(ColorError::InvalidDigit('x')));
+        assert_eq!(hex_to_bin(&"f".repeat(17)), Err(ColorError::Overflow));
+    }
+
+    #[test]
+    fn parses_colors() {
+        let color = Color {
+            r: 0x70,
+            g: 0xc7,
+            b: 0x10,
+        };
+        assert_eq!(Color::from_string("#70c710"), Ok(color));
+        assert_eq!(Color::from_string("70C710"), Ok(color));
+        assert_eq!("#70c710".parse::<Color>(), Ok(color));
+        assert_eq!(
+            Color::from_string("#fa0"),
+            Ok(Color {
+                r: 0xff,
+                g: 0xaa,
+                b: 0x00
+            })
+        );
+
+        assert_eq!(
+            Color::from_string("#70c71"),
+            Err(ColorError::WrongLength(5))
+        );
+        assert_eq!(
+            Color::from_string("#70c7100"),
+            Err(ColorError::WrongLength(7))
+        );
+        assert_eq!(
+            Color::from_string("##70c710"),
+            Err(ColorError::InvalidDigit('#'))
+        );
+        assert_eq!(Color::from_string(""), Err(ColorError::WrongLength(0)));
+    }
+
+    #[test]
+    fn displays_as_hex() {
+        let color = Color::from_string("#0A7f3c").unwrap();
+        assert_eq!(color.to_string(), "#0a7f3c");
+        assert_eq!(color.to_string().parse(), Ok(color));
+    }
+}
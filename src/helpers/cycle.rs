@@ -0,0 +1,244 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// The states repeat from step `start` on, every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The first step with the same state as step `n`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+/// Finds where repeatedly applying `step` to `start` starts going round in
+/// a cycle, using Brent's algorithm.
+///
+/// Only keeps two states at a time, but runs `step` a few more times than
+/// `find_cycle_hashed`. Never returns if the states don't repeat.
+pub fn find_cycle<S, F>(start: S, mut step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: FnMut(&mut S),
+{
+    // find the period, by moving the tortoise up to the hare at every power
+    // of two
+    let (mut power, mut period) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    step(&mut hare);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        step(&mut hare);
+        period += 1;
+    }
+
+    // then the start, with the hare a period ahead of the tortoise
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        step(&mut hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+fn fingerprint<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+// steps `state` until it repeats, remembering `key` of each state, or until
+// it has taken `limit` steps. Returns the cycle and how many steps were taken.
+fn step_until_repeat<S, K, F>(
+    state: &mut S,
+    step: &mut F,
+    limit: usize,
+    key: impl Fn(&S) -> K,
+) -> (Option<Cycle>, usize)
+where
+    K: Hash + Eq,
+    F: FnMut(&mut S),
+{
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    for n in 0..limit {
+        let key = key(state);
+        if let Some(&start) = seen.get(&key) {
+            let period = n - start;
+            return (Some(Cycle { start, period }), n);
+        }
+        seen.insert(key, n);
+        step(state);
+    }
+    (None, limit)
+}
+
+/// Like `find_cycle`, but remembers a 64 bit hash of every state so `step`
+/// only runs until the first repeat.
+///
+/// Two different states with the same hash would give a wrong answer, which
+/// is very unlikely.
+pub fn find_cycle_hashed<S, F>(mut start: S, mut step: F) -> Cycle
+where
+    S: Hash,
+    F: FnMut(&mut S),
+{
+    step_until_repeat(&mut start, &mut step, usize::MAX, fingerprint)
+        .0
+        .unwrap()
+}
+
+// the state `n` steps after `state`, which `step_until_repeat` has just
+// left at `taken` steps
+fn skip_cycles<S, F>(state: &mut S, step: &mut F, found: (Option<Cycle>, usize), n: usize)
+where
+    F: FnMut(&mut S),
+{
+    if let (Some(cycle), taken) = found {
+        // `state` is at step `taken`, the same as `cycle.start`
+        for _ in 0..(n - taken) % cycle.period {
+            step(state);
+        }
+    }
+}
+
+/// The state after `n` steps, skipping every whole cycle once the states
+/// start repeating.
+///
+/// Finds the cycle with `find_cycle`, so only two states are kept at a
+/// time, then takes the steps up to the equivalent of step `n` again. Never
+/// returns if the states don't repeat, even for a small `n`.
+pub fn fast_forward<S, F>(state: S, mut step: F, n: usize) -> S
+where
+    S: Clone + PartialEq,
+    F: FnMut(&mut S),
+{
+    let cycle = find_cycle(state.clone(), &mut step);
+    let mut state = state;
+    for _ in 0..cycle.equivalent_step(n) {
+        step(&mut state);
+    }
+    state
+}
+
+/// Like `fast_forward`, but keeps a copy of every state it passes, so it
+/// runs `step` fewer times and stops after `n` steps if nothing repeats.
+pub fn fast_forward_stored<S, F>(mut state: S, mut step: F, n: usize) -> S
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&mut S),
+{
+    let found = step_until_repeat(&mut state, &mut step, n, S::clone);
+    skip_cycles(&mut state, &mut step, found, n);
+    state
+}
+
+/// Like `fast_forward`, but only keeps a 64 bit hash of each state, with the
+/// same small chance of a wrong answer as `find_cycle_hashed`.
+pub fn fast_forward_hashed<S, F>(mut state: S, mut step: F, n: usize) -> S
+where
+    S: Hash,
+    F: FnMut(&mut S),
+{
+    let found = step_until_repeat(&mut state, &mut step, n, fingerprint);
+    skip_cycles(&mut state, &mut step, found, n);
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // doubling mod 13 goes round all of 1..13, starting from 20 so the first
+    // step isn't part of the cycle: 20, 7, 1, 2, 4, 8, 3, ..., 10, 7
+    fn step(x: &mut u64) {
+        *x = if *x > 13 { *x - 13 } else { *x * 2 % 13 };
+    }
+
+    #[test]
+    fn finds_cycles() {
+        let cycle = find_cycle(20u64, step);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 1,
+                period: 12
+            }
+        );
+        assert_eq!(find_cycle_hashed(20u64, step), cycle);
+
+        assert_eq!(cycle.equivalent_step(0), 0);
+        assert_eq!(cycle.equivalent_step(13), 1);
+        assert_eq!(cycle.equivalent_step(1_000_000), 4);
+
+        // a cycle of one
+        assert_eq!(
+            find_cycle(0u64, step),
+            Cycle {
+                start: 0,
+                period: 1
+            }
+        );
+    }
+
+    #[test]
+    fn fast_forwards() {
+        let slow = |n: usize| {
+            let mut x = 20u64;
+            (0..n).for_each(|_| step(&mut x));
+            x
+        };
+        for n in [0, 1, 5, 13, 14, 100, 12345] {
+            assert_eq!(fast_forward(20u64, step, n), slow(n));
+        }
+        assert_eq!(
+            fast_forward(20u64, step, 1_000_000_000),
+            slow(1_000_000_000 % 12 + 12)
+        );
+        assert_eq!(fast_forward_stored(20u64, step, 12345), slow(12345));
+        assert_eq!(fast_forward_hashed(20u64, step, 12345), slow(12345));
+    }
+
+    // every value hashes the same, so only comparing the states tells them
+    // apart
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Collides(u64);
+
+    impl Hash for Collides {
+        fn hash<H: Hasher>(&self, _: &mut H) {}
+    }
+
+    #[test]
+    fn compares_states_not_hashes() {
+        let collides_step = |x: &mut Collides| step(&mut x.0);
+        for n in [5, 1_000_000] {
+            let Collides(x) = fast_forward_stored(Collides(20), collides_step, n);
+            assert_eq!(x, fast_forward(20u64, step, n));
+        }
+        // the hashed version takes the second state for the first again
+        assert_eq!(
+            fast_forward_hashed(Collides(20), collides_step, 5),
+            Collides(7)
+        );
+    }
+}
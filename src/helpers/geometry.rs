@@ -0,0 +1,146 @@
+use super::enums_and_types::Direction;
+use super::math::gcd;
+use super::positions_and_directions::next_position_counted;
+
+/// A lattice point as `(row, col)`, signed so polygons can go anywhere.
+pub type Point = (i64, i64);
+
+/// Twice the area of the polygon with these vertices, in order. Twice so it
+/// is always a whole number.
+///
+/// Uses the shoelace formula. The last vertex joins back to the first.
+pub fn twice_area(vertices: &[Point]) -> i64 {
+    let n = vertices.len();
+    let signed: i64 = (0..n)
+        .map(|k| {
+            let (row, col) = vertices[k];
+            let (next_row, next_col) = vertices[(k + 1) % n];
+            (col * next_row) - (row * next_col)
+        })
+        .sum();
+    signed.abs()
+}
+
+/// How many lattice points are on the edges of the polygon.
+pub fn boundary_points(vertices: &[Point]) -> i64 {
+    let n = vertices.len();
+    (0..n)
+        .map(|k| {
+            let (row, col) = vertices[k];
+            let (next_row, next_col) = vertices[(k + 1) % n];
+            gcd((next_row - row).abs(), (next_col - col).abs())
+        })
+        .sum()
+}
+
+/// How many lattice points are strictly inside the polygon, using Pick's
+/// theorem: `area = interior + boundary / 2 - 1`.
+pub fn interior_points(vertices: &[Point]) -> i64 {
+    (twice_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointLocation {
+    Inside,
+    OnBoundary,
+    Outside,
+}
+
+/// Where `point` is compared to the polygon, by counting how many edges a
+/// ray going right from it crosses.
+pub fn locate_point(vertices: &[Point], (row, col): Point) -> PointLocation {
+    let n = vertices.len();
+    let mut inside = false;
+
+    for k in 0..n {
+        let (r1, c1) = vertices[k];
+        let (r2, c2) = vertices[(k + 1) % n];
+
+        // on the edge if it's collinear and between the two ends
+        let cross = (r2 - r1) * (col - c1) - (c2 - c1) * (row - r1);
+        if cross == 0
+            && row >= r1.min(r2)
+            && row <= r1.max(r2)
+            && col >= c1.min(c2)
+            && col <= c1.max(c2)
+        {
+            return PointLocation::OnBoundary;
+        }
+
+        // half open on the rows, so a vertex on the ray is only counted once
+        if (r1 > row) != (r2 > row) {
+            // the column where the edge crosses the row, compared without
+            // dividing: col < c1 + (row - r1) * (c2 - c1) / (r2 - r1)
+            let lhs = (col - c1) * (r2 - r1);
+            let rhs = (row - r1) * (c2 - c1);
+            if (r2 > r1 && lhs < rhs) || (r2 < r1 && lhs > rhs) {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        PointLocation::Inside
+    } else {
+        PointLocation::Outside
+    }
+}
+
+/// Builds the vertices of the polygon traced by walking `steps` from
+/// `start`, each step going a number of tiles in a direction.
+pub fn rectilinear_polygon<I>(start: Point, steps: I) -> Vec<Point>
+where
+    I: IntoIterator<Item = (Direction, i64)>,
+{
+    let mut vertices = vec![start];
+    for (dir, count) in steps {
+        let next = next_position_counted(*vertices.last().unwrap(), dir, count);
+        vertices.push(next);
+    }
+    if vertices.len() > 1 && vertices.last() == vertices.first() {
+        vertices.pop();
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_polygons() {
+        // a 4x3 rectangle, and a triangle with a slanted side
+        let rectangle = [(0, 0), (0, 4), (3, 4), (3, 0)];
+        assert_eq!(twice_area(&rectangle), 24);
+        assert_eq!(boundary_points(&rectangle), 14);
+        assert_eq!(interior_points(&rectangle), 6);
+
+        let triangle = [(0, 0), (0, 4), (4, 0)];
+        assert_eq!(twice_area(&triangle), 16);
+        assert_eq!(boundary_points(&triangle), 12);
+        assert_eq!(interior_points(&triangle), 3);
+    }
+
+    #[test]
+    fn locates_points() {
+        let l_shape = [(0, 0), (0, 4), (2, 4), (2, 2), (4, 2), (4, 0)];
+        assert_eq!(locate_point(&l_shape, (1, 1)), PointLocation::Inside);
+        assert_eq!(locate_point(&l_shape, (3, 1)), PointLocation::Inside);
+        assert_eq!(locate_point(&l_shape, (3, 3)), PointLocation::Outside);
+        assert_eq!(locate_point(&l_shape, (2, 3)), PointLocation::OnBoundary);
+        assert_eq!(locate_point(&l_shape, (0, 0)), PointLocation::OnBoundary);
+        assert_eq!(locate_point(&l_shape, (2, -1)), PointLocation::Outside);
+    }
+
+    #[test]
+    fn builds_rectilinear_polygons() {
+        let steps = [
+            (Direction::Right, 4),
+            (Direction::Down, 3),
+            (Direction::Left, 4),
+            (Direction::Up, 3),
+        ];
+        let vertices = rectilinear_polygon((0, 0), steps);
+        assert_eq!(vertices, vec![(0, 0), (0, 4), (3, 4), (3, 0)]);
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+/// A graph whose nodes are labelled by `L`.
+///
+/// Labels are interned to dense indices as they are added, so every
+/// algorithm works on plain `usize` nodes and only goes back to the label
+/// when asked to.
+#[derive(Debug, Clone)]
+pub struct Graph<L> {
+    directed: bool,
+    labels: Vec<L>,
+    indices: HashMap<L, usize>,
+    // (node, weight) for every edge leaving a node
+    adjacency: Vec<Vec<(usize, usize)>>,
+    // every edge as it was added, undirected edges only once
+    edges: Vec<(usize, usize, usize)>,
+}
+
+impl<L: Clone + Eq + Hash> Graph<L> {
+    pub fn new_directed() -> Self {
+        Self::new(true)
+    }
+
+    pub fn new_undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Graph {
+            directed,
+            labels: Vec::new(),
+            indices: HashMap::new(),
+            adjacency: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// number of nodes
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Returns the index of `label`, adding it as a new node if needed.
+    pub fn intern(&mut self, label: L) -> usize {
+        if let Some(&index) = self.indices.get(&label) {
+            return index;
+        }
+        let index = self.labels.len();
+        self.indices.insert(label.clone(), index);
+        self.labels.push(label);
+        self.adjacency.push(Vec::new());
+        index
+    }
+
+    pub fn index_of(&self, label: &L) -> Option<usize> {
+        self.indices.get(label).copied()
+    }
+
+    pub fn label(&self, node: usize) -> &L {
+        &self.labels[node]
+    }
+
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+
+    /// Adds an edge between two labels, interning them if needed.
+    pub fn add_edge(&mut self, from: L, to: L, weight: usize) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        self.add_edge_index(from, to, weight);
+    }
+
+    pub fn add_edge_index(&mut self, from: usize, to: usize, weight: usize) {
+        self.adjacency[from].push((to, weight));
+        if !self.directed && from != to {
+            self.adjacency[to].push((from, weight));
+        }
+        self.edges.push((from, to, weight));
+    }
+
+    /// `(node, weight)` for every edge leaving `node`.
+    pub fn neighbours(&self, node: usize) -> &[(usize, usize)] {
+        &self.adjacency[node]
+    }
+
+    /// Every edge as `(from, to, weight)`, undirected edges are listed once.
+    pub fn edges(&self) -> &[(usize, usize, usize)] {
+        &self.edges
+    }
+
+    /// Groups the nodes into connected components, ignoring edge direction.
+    ///
+    /// Components are ordered by their smallest node, and the nodes inside
+    /// each component are sorted.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut sets = DisjointSet::new(self.len());
+        for &(from, to, _) in self.edges.iter() {
+            sets.union(from, to);
+        }
+
+        let mut component_of_root = HashMap::new();
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        for node in 0..self.len() {
+            let root = sets.find(node);
+            let index = *component_of_root.entry(root).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[index].push(node);
+        }
+        components
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        if self.directed {
+            return self.topological_sort().is_none();
+        }
+
+        let mut sets = DisjointSet::new(self.len());
+        self.edges
+            .iter()
+            .any(|&(from, to, _)| !sets.union(from, to))
+    }
+
+    /// Orders the nodes so every edge goes forwards, or `None` if there is
+    /// a cycle. Undirected graphs only have an order if they have no edges.
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        if !self.directed && !self.edges.is_empty() {
+            return None;
+        }
+
+        let mut in_degree = vec![0; self.len()];
+        for &(_, to, _) in self.edges.iter() {
+            in_degree[to] += 1;
+        }
+
+        let mut queue: VecDeque<usize> = (0..self.len()).filter(|&n| in_degree[n] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &(next, _) in self.adjacency[node].iter() {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if order.len() == self.len() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+
+    /// The minimum total weight of edges that have to be removed so that
+    /// `sink` can't be reached from `source`, with the nodes still
+    /// reachable from `source` after they are removed.
+    ///
+    /// Uses Edmonds-Karp, edge weights are the capacities.
+    pub fn min_cut_between(&self, source: usize, sink: usize) -> (usize, Vec<usize>) {
+        assert_ne!(source, sink, "source and sink must be different nodes");
+
+        // arcs are stored in pairs so `arc ^ 1` is always the reverse arc
+        let mut arcs_from = vec![Vec::new(); self.len()];
+        let mut arc_to = Vec::with_capacity(self.edges.len() * 2);
+        let mut capacity = Vec::with_capacity(self.edges.len() * 2);
+        for &(from, to, weight) in self.edges.iter() {
+            arcs_from[from].push(arc_to.len());
+            arc_to.push(to);
+            capacity.push(weight);
+
+            arcs_from[to].push(arc_to.len());
+            arc_to.push(from);
+            capacity.push(if self.directed { 0 } else { weight });
+        }
+
+        let mut flow = 0;
+        loop {
+            let mut arc_into: Vec<Option<usize>> = vec![None; self.len()];
+            let mut seen = vec![false; self.len()];
+            seen[source] = true;
+
+            let mut queue = VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                if node == sink {
+                    break;
+                }
+                for &arc in arcs_from[node].iter() {
+                    let next = arc_to[arc];
+                    if !seen[next] && capacity[arc] > 0 {
+                        seen[next] = true;
+                        arc_into[next] = Some(arc);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            if !seen[sink] {
+                let source_side = (0..self.len()).filter(|&n| seen[n]).collect();
+                return (flow, source_side);
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+            while let Some(arc) = arc_into[node] {
+                path.push(arc);
+                node = arc_to[arc ^ 1];
+            }
+
+            let bottleneck = path.iter().map(|&arc| capacity[arc]).min().unwrap();
+            for arc in path {
+                capacity[arc] -= bottleneck;
+                capacity[arc ^ 1] += bottleneck;
+            }
+            flow += bottleneck;
+        }
+    }
+
+    /// The global minimum cut of an undirected graph, as the weight of the
+    /// cut and the nodes on one side of it.
+    pub fn min_cut(&self) -> (usize, Vec<usize>) {
+        assert!(!self.directed, "min_cut only works on undirected graphs");
+        assert!(self.len() >= 2, "min_cut needs at least two nodes");
+
+        // node 0 is on one side of every cut, so one of the other nodes
+        // has to be on the other side
+        (1..self.len())
+            .map(|sink| self.min_cut_between(0, sink))
+            .min_by_key(|(cut, _)| *cut)
+            .unwrap()
+    }
+}
+
+impl<L: Clone + Eq + Hash + Display> Graph<L> {
+    /// Writes the graph in graphviz's DOT format, weights other than 1 are
+    /// shown as edge labels.
+    pub fn to_dot(&self) -> String {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        let mut dot = format!("{kind} {{\n");
+        for label in self.labels.iter() {
+            writeln!(dot, "    \"{label}\";").unwrap();
+        }
+        for &(from, to, weight) in self.edges.iter() {
+            let (from, to) = (&self.labels[from], &self.labels[to]);
+            if weight == 1 {
+                writeln!(dot, "    \"{from}\" {arrow} \"{to}\";").unwrap();
+            } else {
+                writeln!(dot, "    \"{from}\" {arrow} \"{to}\" [label={weight}];").unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        DisjointSet {
+            parents: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+
+    /// returns false if the two nodes were already joined
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        self.parents[b] = a;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected(edges: &[(&'static str, &'static str)]) -> Graph<&'static str> {
+        let mut graph = Graph::new_undirected();
+        for &(from, to) in edges {
+            graph.add_edge(from, to, 1);
+        }
+        graph
+    }
+
+    #[test]
+    fn interns_labels() {
+        let mut graph = undirected(&[("a", "b"), ("b", "c")]);
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.intern("b"), 1);
+        assert_eq!(graph.index_of(&"c"), Some(2));
+        assert_eq!(graph.index_of(&"d"), None);
+        assert_eq!(graph.neighbours(1), &[(0, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn finds_components_and_cycles() {
+        let mut graph = undirected(&[("a", "b"), ("c", "d"), ("d", "e")]);
+        graph.intern("f");
+        assert_eq!(
+            graph.connected_components(),
+            vec![vec![0, 1], vec![2, 3, 4], vec![5]]
+        );
+        assert!(!graph.has_cycle());
+
+        graph.add_edge("e", "c", 1);
+        assert!(graph.has_cycle());
+    }
+
+    #[test]
+    fn sorts_topologically() {
+        let mut graph = Graph::new_directed();
+        graph.add_edge("shirt", "tie", 1);
+        graph.add_edge("tie", "jacket", 1);
+        graph.add_edge("trousers", "shoes", 1);
+        graph.add_edge("trousers", "belt", 1);
+        graph.add_edge("belt", "jacket", 1);
+
+        let order = graph.topological_sort().unwrap();
+        for &(from, to, _) in graph.edges() {
+            let pos = |n| order.iter().position(|&x| x == n).unwrap();
+            assert!(pos(from) < pos(to));
+        }
+        assert!(!graph.has_cycle());
+
+        graph.add_edge("jacket", "shirt", 1);
+        assert_eq!(graph.topological_sort(), None);
+        assert!(graph.has_cycle());
+    }
+
+    #[test]
+    fn finds_min_cut() {
+        // two triangles joined by a single bridge
+        let graph = undirected(&[
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("f", "d"),
+        ]);
+        let (cut, side) = graph.min_cut();
+        assert_eq!(cut, 1);
+        assert_eq!(side, vec![0, 1, 2]);
+
+        let mut weighted = Graph::new_directed();
+        weighted.add_edge("s", "a", 3);
+        weighted.add_edge("s", "b", 2);
+        weighted.add_edge("a", "t", 2);
+        weighted.add_edge("b", "t", 3);
+        weighted.add_edge("a", "b", 1);
+        let (cut, _) = weighted.min_cut_between(0, 3);
+        assert_eq!(cut, 5);
+    }
+
+    #[test]
+    fn writes_dot() {
+        let mut graph = Graph::new_directed();
+        graph.add_edge("a", "b", 1);
+        graph.add_edge("b", "a", 4);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    \"a\";\n    \"b\";\n    \"a\" -> \"b\";\n    \"b\" -> \"a\" [label=4];\n}\n"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use std::cmp::{max, min};
+use std::ops::{Add, Mul, Range, Sub};
+
+/// A set of values stored as sorted, disjoint, half open ranges.
+///
+/// Ranges that touch are merged and empty ranges are dropped, so two sets
+/// holding the same values always compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    fn normalized(mut ranges: Vec<Range<T>>) -> Self {
+        ranges.retain(|r| r.start < r.end);
+        ranges.sort_by_key(|r| r.start);
+
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = max(last.end, range.end),
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges.iter().any(|r| r.contains(&value))
+    }
+
+    /// the smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = Self::normalized(ranges);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalized([&self.ranges[..], &other.ranges[..]].concat())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let (start, end) = (max(a.start, b.start), min(a.end, b.end));
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        for range in self.ranges.iter() {
+            let mut start = range.start;
+            for cut in other.ranges.iter() {
+                if cut.end <= start {
+                    continue;
+                }
+                if cut.start >= range.end {
+                    break;
+                }
+                if cut.start > start {
+                    ranges.push(start..cut.start);
+                }
+                start = cut.end;
+                if start >= range.end {
+                    break;
+                }
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// Splits the set into the values below `at`, and the values at or
+    /// above it.
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        let (mut below, mut above) = (Vec::new(), Vec::new());
+        for range in self.ranges.iter() {
+            if range.end <= at {
+                below.push(range.clone());
+            } else if range.start >= at {
+                above.push(range.clone());
+            } else {
+                below.push(range.start..at);
+                above.push(at..range.end);
+            }
+        }
+        (IntervalSet { ranges: below }, IntervalSet { ranges: above })
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Default,
+{
+    /// how many values are in the set
+    pub fn size(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::default(), |z, r| z + (r.end - r.start))
+    }
+
+    /// The image of every value in the set under `function`.
+    pub fn map_through(&self, function: &PiecewiseLinear<T>) -> Self {
+        let mut unmapped = self.clone();
+        let mut mapped = Vec::new();
+
+        for (src, dest_start) in function.pieces.iter() {
+            let src = IntervalSet::from(src.clone());
+            for range in unmapped.intersection(&src).iter() {
+                let start = *dest_start + (range.start - src.ranges[0].start);
+                mapped.push(start..start + (range.end - range.start));
+            }
+            unmapped = unmapped.difference(&src);
+        }
+
+        mapped.extend(unmapped.ranges);
+        Self::normalized(mapped)
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::normalized(vec![range])
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        Self::normalized(iter.into_iter().collect())
+    }
+}
+
+/// A function made of pieces that each shift a range of values onto
+/// another range of the same length (linear with a slope of one).
+///
+/// Values outside every piece map to themselves. If pieces overlap, the
+/// one added first wins.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PiecewiseLinear<T> {
+    // (source range, where the start of the source range ends up)
+    pieces: Vec<(Range<T>, T)>,
+}
+
+impl<T> PiecewiseLinear<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Default,
+{
+    pub fn new() -> Self {
+        PiecewiseLinear { pieces: Vec::new() }
+    }
+
+    /// maps `src` onto the range starting at `dest_start`
+    pub fn add_piece(&mut self, src: Range<T>, dest_start: T) {
+        self.pieces.push((src, dest_start));
+    }
+
+    pub fn pieces(&self) -> &[(Range<T>, T)] {
+        &self.pieces
+    }
+
+    pub fn apply(&self, value: T) -> T {
+        self.pieces
+            .iter()
+            .find(|(src, _)| src.contains(&value))
+            .map_or(value, |(src, dest_start)| *dest_start + (value - src.start))
+    }
+
+    // the pieces with the overlaps taken out of the later ones, sorted
+    fn disjoint_pieces(&self) -> Vec<(Range<T>, T)> {
+        let mut covered = IntervalSet::new();
+        let mut pieces = Vec::new();
+        for (src, dest_start) in self.pieces.iter() {
+            let src_set = IntervalSet::from(src.clone());
+            for range in src_set.difference(&covered).iter() {
+                pieces.push((range.clone(), *dest_start + (range.start - src.start)));
+            }
+            covered = covered.union(&src_set);
+        }
+        pieces.sort_by_key(|(src, _)| src.start);
+        pieces
+    }
+
+    /// The function that applies `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let (first, second) = (self.disjoint_pieces(), next.disjoint_pieces());
+        // anything outside both sets of pieces goes through unchanged
+        let domain: IntervalSet<T> = first
+            .iter()
+            .chain(second.iter())
+            .map(|(src, _)| src.clone())
+            .collect();
+
+        let mut composed = PiecewiseLinear::new();
+        for range in domain.iter() {
+            for (part, image) in split_through(&first, range.clone()) {
+                let image_range = image..image + (part.end - part.start);
+                for (image_part, dest_start) in split_through(&second, image_range) {
+                    let start = part.start + (image_part.start - image);
+                    if start != dest_start {
+                        let end = start + (image_part.end - image_part.start);
+                        composed.add_piece(start..end, dest_start);
+                    }
+                }
+            }
+        }
+        composed
+    }
+
+    /// Every value that `apply` maps into `set`.
+    pub fn preimage(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let pieces = self.disjoint_pieces();
+        let sources: IntervalSet<T> = pieces.iter().map(|(src, _)| src.clone()).collect();
+
+        let mut ranges = set.difference(&sources).ranges;
+        for (src, dest_start) in pieces.iter() {
+            let dest = IntervalSet::from(*dest_start..*dest_start + (src.end - src.start));
+            for range in set.intersection(&dest).iter() {
+                let start = src.start + (range.start - *dest_start);
+                ranges.push(start..start + (range.end - range.start));
+            }
+        }
+        IntervalSet::normalized(ranges)
+    }
+}
+
+// splits `range` into the parts covered by each of the sorted, disjoint
+// `pieces` and the gaps between them, with where each part starts mapping to
+fn split_through<T>(pieces: &[(Range<T>, T)], range: Range<T>) -> Vec<(Range<T>, T)>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    let mut parts = Vec::new();
+    let mut start = range.start;
+    for (src, dest_start) in pieces.iter() {
+        if src.end <= start {
+            continue;
+        }
+        if src.start >= range.end {
+            break;
+        }
+        if src.start > start {
+            parts.push((start..src.start, start));
+            start = src.start;
+        }
+        let end = min(src.end, range.end);
+        parts.push((start..end, *dest_start + (start - src.start)));
+        start = end;
+    }
+    if start < range.end {
+        parts.push((start..range.end, start));
+    }
+    parts
+}
+
+/// An axis aligned box in `N` dimensions, with a half open range on each
+/// axis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalBox<T, const N: usize> {
+    pub axes: [Range<T>; N],
+}
+
+impl<T: Copy + Ord, const N: usize> IntervalBox<T, N> {
+    pub fn new(axes: [Range<T>; N]) -> Self {
+        IntervalBox { axes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|r| r.start >= r.end)
+    }
+
+    pub fn contains(&self, point: [T; N]) -> bool {
+        self.axes.iter().zip(point).all(|(r, p)| r.contains(&p))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut axes = self.axes.clone();
+        for (axis, range) in axes.iter_mut().enumerate() {
+            let other = &other.axes[axis];
+            *range = max(range.start, other.start)..min(range.end, other.end);
+        }
+        let intersection = IntervalBox { axes };
+        if intersection.is_empty() {
+            None
+        } else {
+            Some(intersection)
+        }
+    }
+
+    /// Splits the box along `axis`, into the part below `at` and the part at
+    /// or above it, `None` for a part that would be empty.
+    pub fn split_at(&self, axis: usize, at: T) -> (Option<Self>, Option<Self>) {
+        let range = &self.axes[axis];
+        let mut below = self.clone();
+        below.axes[axis] = range.start..min(range.end, at);
+        let mut above = self.clone();
+        above.axes[axis] = max(range.start, at)..range.end;
+
+        let non_empty = |b: Self| if b.is_empty() { None } else { Some(b) };
+        (non_empty(below), non_empty(above))
+    }
+
+    /// how many points are in the box, counted in `V` so it doesn't overflow
+    /// `T`
+    pub fn volume<V>(&self) -> V
+    where
+        T: Into<V>,
+        V: Copy + Sub<Output = V> + Mul<Output = V> + From<u8>,
+    {
+        if self.is_empty() {
+            return V::from(0);
+        }
+        self.axes
+            .iter()
+            .fold(V::from(1), |z, r| z * (r.end.into() - r.start.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_ranges() {
+        let set: IntervalSet<i32> = [5..8, 1..3, 3..4, 7..10, 12..12].into_iter().collect();
+        assert_eq!(set.ranges(), &[1..4, 5..10]);
+        assert_eq!(set.size(), 8);
+        assert_eq!(set.min(), Some(1));
+        assert!(set.contains(9) && !set.contains(4));
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a: IntervalSet<i32> = [0..5, 10..15].into_iter().collect();
+        let b: IntervalSet<i32> = [3..12, 14..20].into_iter().collect();
+
+        assert_eq!(a.union(&b), IntervalSet::from(0..20));
+        assert_eq!(a.intersection(&b).ranges(), &[3..5, 10..12, 14..15]);
+        assert_eq!(a.difference(&b).ranges(), &[0..3, 12..14]);
+        assert_eq!(b.difference(&a).ranges(), &[5..10, 15..20]);
+
+        let (below, above) = a.split_at(12);
+        assert_eq!(below.ranges(), &[0..5, 10..12]);
+        assert_eq!(above, IntervalSet::from(12..15));
+    }
+
+    #[test]
+    fn maps_through_pieces() {
+        let mut function = PiecewiseLinear::new();
+        function.add_piece(98..100, 50);
+        function.add_piece(50..98, 52);
+
+        assert_eq!(function.apply(79), 81);
+        assert_eq!(function.apply(99), 51);
+        assert_eq!(function.apply(10), 10);
+
+        let set: IntervalSet<u64> = [45..55, 97..102].into_iter().collect();
+        // 98..100 -> 50..52, 50..55 -> 52..57 and 97..98 -> 99..100
+        assert_eq!(set.map_through(&function).ranges(), &[45..57, 99..102]);
+    }
+
+    #[test]
+    fn composes_and_inverts() {
+        let mut first = PiecewiseLinear::new();
+        first.add_piece(0..10, 100);
+        first.add_piece(5..20, 0);
+        let mut second = PiecewiseLinear::new();
+        second.add_piece(100..105, 200);
+        second.add_piece(12..15, 50);
+
+        let composed = first.then(&second);
+        for x in 0..130u64 {
+            assert_eq!(composed.apply(x), second.apply(first.apply(x)), "at {x}");
+        }
+
+        // 17 goes to 12 and on to 50, and 50 isn't moved by either
+        let image = IntervalSet::from(50..51);
+        assert_eq!(composed.preimage(&image).ranges(), &[17..18, 50..51]);
+
+        let image: IntervalSet<u64> = [0..8, 50..53, 200..203].into_iter().collect();
+        let by_hand: IntervalSet<u64> = (0..300)
+            .filter(|&x| image.contains(composed.apply(x)))
+            .map(|x| x..x + 1)
+            .collect();
+        let preimage = composed.preimage(&image);
+        assert_eq!(preimage.intersection(&IntervalSet::from(0..300)), by_hand);
+    }
+
+    #[test]
+    fn splits_boxes() {
+        let b = IntervalBox::new([1..11u16, 1..11]);
+        assert_eq!(b.volume::<u64>(), 100);
+
+        let (below, above) = b.split_at(0, 4);
+        assert_eq!(below.unwrap().axes, [1..4, 1..11]);
+        assert_eq!(above.unwrap().axes, [4..11, 1..11]);
+        assert_eq!(b.split_at(1, 1).0, None);
+
+        let other = IntervalBox::new([5..20, 0..3]);
+        assert_eq!(b.intersection(&other).unwrap().axes, [5..11, 1..3]);
+        assert!(b.contains([10, 10]) && !b.contains([11, 1]));
+    }
+}
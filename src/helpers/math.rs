@@ -0,0 +1,253 @@
+pub fn gcd<T>(mut a: T, mut b: T) -> T
+where
+    T: std::ops::Rem<Output = T> + Default + std::cmp::PartialEq + Copy,
+{
+    while b != T::default() {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+pub fn lcm<T>(a: T, b: T) -> T
+where
+    T: std::ops::Rem<Output = T>
+        + std::ops::Div<Output = T>
+        + std::ops::Mul<Output = T>
+        + Default
+        + std::cmp::PartialEq
+        + Copy,
+{
+    a / gcd(a, b) * b
+}
+
+/// Any primitive integer. The functions below do their work in `i128` and
+/// convert back, so they can't overflow halfway through.
+pub trait Integer: Copy + Ord + TryFrom<i128> + TryInto<i128> {}
+impl<T: Copy + Ord + TryFrom<i128> + TryInto<i128>> Integer for T {}
+
+fn widen<T: Integer>(x: T) -> i128 {
+    x.try_into().ok().expect("integer fits in an i128")
+}
+
+fn narrow<T: Integer>(x: i128) -> Option<T> {
+    T::try_from(x).ok()
+}
+
+/// Returns `(g, x, y)` where `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `a * b mod m` in `0..m`, without overflowing when `a * b` doesn't fit.
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    assert!(m > 0, "modulus must be positive");
+    let (mut a, mut b, m) = (a.rem_euclid(m) as u128, b.rem_euclid(m) as u128, m as u128);
+    if let Some(product) = a.checked_mul(b) {
+        return (product % m) as i128;
+    }
+
+    // double and add, every step stays below 2 * m
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = (result + a) % m;
+        }
+        a = (a << 1) % m;
+        b >>= 1;
+    }
+    result as i128
+}
+
+// `a + b mod m` for `a` and `b` already in `0..m`, without overflowing
+fn add_mod(a: i128, b: i128, m: i128) -> i128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// The `x` in `0..m` with `a * x = 1 mod m`, if there is one.
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    let m = widen(m);
+    assert!(m > 0, "modulus must be positive");
+    let (g, x, _) = extended_gcd(widen(a).rem_euclid(m), m);
+    if g != 1 {
+        return None;
+    }
+    narrow(x.rem_euclid(m))
+}
+
+/// `lcm` that returns `None` instead of overflowing.
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    let (a, b) = (widen(a), widen(b));
+    if a == 0 || b == 0 {
+        return narrow(0);
+    }
+    let lcm = (a / gcd(a, b)).checked_mul(b)?;
+    narrow(lcm.abs())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    /// the congruences contradict each other
+    NoSolution,
+    /// the combined modulus doesn't fit in the integer type
+    Overflow,
+}
+
+/// Solves `x = r mod m` for every `(r, m)` in `congruences`, returning the
+/// smallest non-negative `x` and the combined modulus.
+///
+/// The moduli don't have to be coprime.
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Result<(T, T), CrtError> {
+    let (mut x, mut modulus): (i128, i128) = (0, 1);
+
+    for &(r, m) in congruences {
+        let m = widen(m);
+        assert!(m > 0, "modulus must be positive");
+        let r = widen(r).rem_euclid(m);
+
+        let (g, p, _) = extended_gcd(modulus, m);
+        let diff = r - x;
+        if diff % g != 0 {
+            return Err(CrtError::NoSolution);
+        }
+
+        // x + modulus * k = r mod m, so k = (diff / g) * p mod (m / g)
+        let step = m / g;
+        let k = mul_mod(diff / g, p, step);
+        let new_modulus = (modulus / g).checked_mul(m).ok_or(CrtError::Overflow)?;
+        x = add_mod(x, mul_mod(modulus, k, new_modulus), new_modulus);
+        modulus = new_modulus;
+    }
+
+    match (narrow(x), narrow(modulus)) {
+        (Some(x), Some(modulus)) => Ok((x, modulus)),
+        _ => Err(CrtError::Overflow),
+    }
+}
+
+/// The largest `x` with `x * x <= n`. Works for all of `u128`, which is why
+/// it doesn't go through `i128` like the functions above.
+pub fn isqrt<T>(n: T) -> T
+where
+    T: Copy + TryInto<u128> + TryFrom<u128>,
+{
+    let n: u128 = n.try_into().ok().expect("square root of a negative number");
+    if n < 2 {
+        return T::try_from(n).ok().unwrap();
+    }
+
+    // newton's method from a guess that's never too small
+    let bits = 128 - n.leading_zeros();
+    let mut x: u128 = 1 << bits.div_ceil(2);
+    loop {
+        let next = (x + n / x) / 2;
+        if next >= x {
+            break;
+        }
+        x = next;
+    }
+    // the root is never bigger than n, so it fits
+    T::try_from(x).ok().unwrap()
+}
+
+/// Given the values of a polynomial at `0, 1, 2, ...`, finds its value at
+/// `x`, which can also be negative or before the end of `values`.
+///
+/// The polynomial is assumed to have a degree less than `values.len()`.
+/// Uses Newton's forward differences, which gives the same answer as
+/// Lagrange interpolation but stays in integers.
+pub fn extrapolate_polynomial<T: Integer>(values: &[T], x: T) -> T {
+    let mut differences: Vec<i128> = values.iter().map(|&v| widen(v)).collect();
+    let x = widen(x);
+
+    // sum of binomial(x, k) * (the k-th difference at 0)
+    let mut total: i128 = 0;
+    let mut binomial: i128 = 1;
+    for k in 0..differences.len() {
+        total += binomial * differences[0];
+        for i in 0..differences.len() - k - 1 {
+            differences[i] = differences[i + 1] - differences[i];
+        }
+        binomial = binomial * (x - k as i128) / (k as i128 + 1);
+    }
+
+    narrow(total).expect("result fits in the integer type")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_inverses() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3i64, 11), Some(7));
+        assert_eq!(mod_inverse(6u32, 9), None);
+        assert_eq!(mul_mod(i128::MAX - 1, i128::MAX - 1, i128::MAX), 1);
+    }
+
+    #[test]
+    fn solves_congruences() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        // moduli that share a factor
+        assert_eq!(crt(&[(3u64, 4), (5, 6)]), Ok((11, 12)));
+        assert_eq!(crt(&[(1u64, 4), (2, 6)]), Err(CrtError::NoSolution));
+        assert_eq!(crt(&[(0u8, 16), (0, 17)]), Err(CrtError::Overflow));
+        assert_eq!(crt::<i32>(&[]), Ok((0, 1)));
+        // x = -1 mod both, with a combined modulus just below i128::MAX
+        let (a, b) = ((1i128 << 63) - 1, (1i128 << 64) - 3);
+        assert_eq!(crt(&[(a - 1, a), (b - 1, b)]), Ok((a * b - 1, a * b)));
+
+        assert_eq!(checked_lcm(4u8, 6), Some(12));
+        assert_eq!(checked_lcm(200u8, 3), None);
+    }
+
+    #[test]
+    fn finds_square_roots() {
+        for n in 0..1000u64 {
+            let r = isqrt(n);
+            assert!(r * r <= n && (r + 1) * (r + 1) > n);
+        }
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(isqrt(i128::MAX), 13043817825332782212);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(
+            isqrt((u64::MAX as u128) * (u64::MAX as u128) - 1),
+            u64::MAX as u128 - 1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "square root of a negative number")]
+    fn no_negative_square_roots() {
+        isqrt(-4i32);
+    }
+
+    #[test]
+    fn extrapolates_polynomials() {
+        assert_eq!(extrapolate_polynomial(&[0, 3, 6, 9, 12, 15], 6), 18);
+        assert_eq!(extrapolate_polynomial(&[10, 13, 16, 21, 30, 45], -1), 5);
+        // x^2 + 1
+        assert_eq!(extrapolate_polynomial(&[1usize, 2, 5], 1000), 1000001);
+        assert_eq!(extrapolate_polynomial(&[1, 2, 5], 1), 2);
+    }
+}
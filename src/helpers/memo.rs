@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// Somewhere to keep answers that have already been worked out.
+pub trait Cache<K, V> {
+    fn get(&mut self, key: &K) -> Option<V>;
+    /// returns true if something else was thrown out to make room
+    fn insert(&mut self, key: K, value: V) -> bool;
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Never forgets anything.
+impl<K: Hash + Eq, V: Clone> Cache<K, V> for HashMap<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        HashMap::get(self, key).cloned()
+    }
+
+    fn insert(&mut self, key: K, value: V) -> bool {
+        HashMap::insert(self, key, value);
+        false
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn clear(&mut self) {
+        HashMap::clear(self)
+    }
+}
+
+/// Keeps at most `capacity` answers, forgetting the least recently used
+/// one first.
+#[derive(Debug, Clone)]
+pub struct Lru<K, V> {
+    capacity: usize,
+    // the value, and when it was last used
+    entries: HashMap<K, (V, u64)>,
+    // when each key was last used, oldest first
+    order: BTreeMap<u64, K>,
+    clock: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "an LRU cache needs room for something");
+        Lru {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn touch(&mut self, key: &K) -> Option<&mut (V, u64)> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        let key = self.order.remove(&entry.1).unwrap();
+        entry.1 = self.clock;
+        self.order.insert(self.clock, key);
+        Some(entry)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Cache<K, V> for Lru<K, V> {
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.touch(key).map(|(value, _)| value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) -> bool {
+        if let Some(entry) = self.touch(&key) {
+            entry.0 = value;
+            return false;
+        }
+
+        let evicted = self.entries.len() == self.capacity;
+        if evicted {
+            let (_, oldest) = self.order.pop_first().unwrap();
+            self.entries.remove(&oldest);
+        }
+        self.order.insert(self.clock, key.clone());
+        self.entries.insert(key, (value, self.clock));
+        evicted
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// the fraction of lookups that were already known, 0 if there were none
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A function that remembers its answers, including for the calls it makes
+/// to itself.
+///
+/// `f` gets a `recur` function to call instead of calling itself, so the
+/// cache doesn't have to be passed down by hand:
+///
+/// ```ignore
+/// let mut fib = Memo::new(|recur: &mut dyn FnMut(u64) -> u64, n: u64| {
+///     if n < 2 { n } else { recur(n - 1) + recur(n - 2) }
+/// });
+/// fib.get(90);
+/// ```
+pub struct Memo<K, V, F, C = HashMap<K, V>> {
+    f: F,
+    cache: C,
+    stats: CacheStats,
+    _types: std::marker::PhantomData<(K, V)>,
+}
+
+impl<K, V, F> Memo<K, V, F>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    pub fn new(f: F) -> Self {
+        Memo::with_cache(HashMap::new(), f)
+    }
+}
+
+impl<K, V, F, C> Memo<K, V, F, C>
+where
+    K: Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+    C: Cache<K, V>,
+{
+    /// Remembers answers in `cache`, like an `Lru` to bound the memory.
+    pub fn with_cache(cache: C, f: F) -> Self {
+        Memo {
+            f,
+            cache,
+            stats: CacheStats::default(),
+            _types: std::marker::PhantomData,
+        }
+    }
+
+    pub fn get(&mut self, key: K) -> V {
+        lookup(&self.f, &mut self.cache, &mut self.stats, key)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// forgets every answer, but keeps the stats
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+// the cached answer for `key`, or `f` worked out with a `recur` that goes
+// back through the cache
+fn lookup<K, V, F, C>(f: &F, cache: &mut C, stats: &mut CacheStats, key: K) -> V
+where
+    K: Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+    C: Cache<K, V>,
+{
+    if let Some(value) = cache.get(&key) {
+        stats.hits += 1;
+        return value;
+    }
+    stats.misses += 1;
+
+    let mut recur = |key: K| lookup(f, cache, stats, key);
+    let value = f(&mut recur, key.clone());
+    if cache.insert(key, value.clone()) {
+        stats.evictions += 1;
+    }
+    value
+}
+
+/// Works out `f(key)` with a cache that only lasts for this call.
+pub fn memoize<K, V, F>(key: K, f: F) -> V
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    Memo::new(f).get(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_recursive_calls() {
+        let mut fib = Memo::new(|recur: &mut dyn FnMut(u64) -> u64, n: u64| {
+            if n < 2 {
+                n
+            } else {
+                recur(n - 1) + recur(n - 2)
+            }
+        });
+        assert_eq!(fib.get(90), 2880067194370816120);
+        // every n is only worked out once
+        assert_eq!(fib.stats().misses, 91);
+        assert_eq!(fib.stats().hits, 88);
+
+        assert_eq!(fib.get(50), 12586269025);
+        assert_eq!(fib.stats().hits, 89);
+        assert!(fib.stats().hit_rate() > 0.49);
+
+        let paths = memoize((0u8, 0u8), |recur, (x, y)| {
+            if x == 10 || y == 10 {
+                1u64
+            } else {
+                recur((x + 1, y)) + recur((x, y + 1))
+            }
+        });
+        assert_eq!(paths, 184756);
+    }
+
+    #[test]
+    fn forgets_the_least_recently_used() {
+        let mut lru = Lru::new(2);
+        assert!(!lru.insert('a', 1));
+        assert!(!lru.insert('b', 2));
+        assert_eq!(lru.get(&'a'), Some(1));
+        // 'b' is the oldest now
+        assert!(lru.insert('c', 3));
+        assert_eq!(lru.get(&'b'), None);
+        assert_eq!(lru.get(&'a'), Some(1));
+        assert_eq!(lru.get(&'c'), Some(3));
+        assert_eq!(lru.len(), 2);
+    }
+
+    #[test]
+    fn works_with_a_bounded_cache() {
+        let mut fib = Memo::with_cache(Lru::new(3), |recur, n: u64| {
+            if n < 2 {
+                n
+            } else {
+                recur(n - 1) + recur(n - 2)
+            }
+        });
+        assert_eq!(fib.get(60), 1548008755920);
+        assert_eq!(fib.cache().len(), 3);
+        assert!(fib.stats().evictions > 0);
+    }
+}
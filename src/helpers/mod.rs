@@ -1,7 +1,5 @@
 #[allow(unused)]
 pub mod constructor {
-    use std::str::FromStr;
-
     pub use grid::Grid;
 
     pub trait FromChar {
@@ -19,13 +17,13 @@ pub mod constructor {
         Grid::from_vec(grid.concat(), grid[0].len())
     }
 
-    use once_cell::sync::Lazy;
-    use regex::Regex;
-    pub fn line_to_digits<T: FromStr + Default>(line: &str) -> Vec<T> {
-        const REGEX: &str = r"(-?\d+)";
-        static RE: Lazy<Regex> = Lazy::new(|| Regex::new(REGEX).unwrap());
-        RE.find_iter(line)
-            .map(|m| m.as_str().trim_end().parse().unwrap_or_default())
+    use super::scan::{numbers, ScanInt};
+    /// Every number in the line, like `-?\d+`. One that doesn't fit in `T`,
+    /// which for unsigned types includes a negative one, is read as 0.
+    pub fn line_to_digits<T: ScanInt>(line: &str) -> Vec<T> {
+        numbers(line)
+            .checked()
+            .map(|value| value.unwrap_or(T::ZERO))
             .collect()
     }
 }
@@ -45,82 +43,58 @@ pub mod array_helpers {
 
 #[allow(unused)]
 pub mod grid_stuff {
+    use super::bitgrid::BitGrid;
     use super::enums_and_types::Position;
     use grid::Grid;
+    use std::collections::VecDeque;
 
     pub fn flood_fill(flooder_grid: &mut Grid<bool>, pos: Position) {
-        let mut flood_stack = vec![pos];
-
-        while let Some((y, x)) = flood_stack.pop() {
-            flooder_grid[(y, x)] = true;
-
-            if x > 0 && (flooder_grid[(y, x - 1)] == false) {
-                flood_stack.push((y, x - 1));
-            }
-            if x < flooder_grid.cols() - 1 && (flooder_grid[(y, x + 1)] == false) {
-                flood_stack.push((y, x + 1));
-            }
+        // the flood goes through the cells that aren't filled yet
+        let mut open = BitGrid::from_grid(flooder_grid, |&filled| !filled);
+        open.set(pos, true);
 
-            if y > 0 && (flooder_grid[(y - 1, x)] == false) {
-                flood_stack.push((y - 1, x));
-            }
-            if y < flooder_grid.rows() - 1 && (flooder_grid[(y + 1, x)] == false) {
-                flood_stack.push((y + 1, x));
-            }
+        for pos in BitGrid::flood_fill(&open, pos).iter_ones() {
+            flooder_grid[pos] = true;
         }
     }
 
-    use std::collections::VecDeque;
-
-    pub fn flood_fill_count<F>(
-        size: (usize, usize),
-        pos: (usize, usize),
-        count: usize,
-        f: F,
-    ) -> Grid<usize>
-    where
-        F: Fn((usize, usize)) -> bool,
-    {
-        let (rows, cols) = size;
-        let mut flooding_grid = Grid::new(rows, cols);
-        let mut flood_stack = VecDeque::new();
-        flood_stack.push_back((pos, count));
-
-        while let Some(((y, x), c)) = flood_stack.pop_front() {
-            flooding_grid[(y, x)] = c;
-
-            if x > 0 && (flooding_grid[(y, x - 1)] < c - 1 && f((y, x - 1))) {
-                flood_stack.push_back(((y, x - 1), c - 1));
-            }
-            if x < cols - 1 && (flooding_grid[(y, x + 1)] < c - 1 && f((y, x + 1))) {
-                flood_stack.push_back(((y, x + 1), c - 1));
-            }
-
-            if y > 0 && (flooding_grid[(y - 1, x)] < c - 1 && f((y - 1, x))) {
-                flood_stack.push_back(((y - 1, x), c - 1));
-            }
-            if y < rows - 1 && (flooding_grid[(y + 1, x)] < c - 1 && f((y + 1, x))) {
-                flood_stack.push_back(((y + 1, x), c - 1));
-            }
-        }
-
-        flooding_grid
-    }
-
-    fn grid_to_bool_grid<T, F>(grid: Grid<T>, f: F) -> Grid<bool>
+    /// How many steps up, down, left or right it takes to get to each cell
+    /// from the closest of `starts`, only going through cells that are
+    /// `passable`. `None` for cells that can't be reached.
+    ///
+    /// The starts are always at distance 0, even if they aren't passable.
+    pub fn distance_map<T, I, F>(grid: &Grid<T>, starts: I, passable: F) -> Grid<Option<u32>>
     where
+        I: IntoIterator<Item = Position>,
         F: Fn(&T) -> bool,
     {
         let (rows, cols) = grid.size();
-        let mut new_grid = Grid::new(rows, cols);
+        let mut distances: Grid<Option<u32>> = Grid::new(rows, cols);
+        let mut queue = VecDeque::new();
+        for pos in starts {
+            if distances[pos].is_none() {
+                distances[pos] = Some(0);
+                queue.push_back(pos);
+            }
+        }
 
-        for j in 0..rows {
-            for i in 0..cols {
-                new_grid[(j, i)] = f(&grid[(j, i)]);
+        while let Some((y, x)) = queue.pop_front() {
+            let next_distance = distances[(y, x)].unwrap() + 1;
+            let neighbours = [
+                y.checked_sub(1).map(|y| (y, x)),
+                (y + 1 < rows).then_some((y + 1, x)),
+                x.checked_sub(1).map(|x| (y, x)),
+                (x + 1 < cols).then_some((y, x + 1)),
+            ];
+            for pos in neighbours.into_iter().flatten() {
+                if distances[pos].is_none() && passable(&grid[pos]) {
+                    distances[pos] = Some(next_distance);
+                    queue.push_back(pos);
+                }
             }
         }
 
-        new_grid
+        distances
     }
 
     pub fn find_index_of<T, F>(grid: &Grid<T>, f: F) -> (usize, usize)
@@ -136,6 +110,29 @@ pub mod grid_stuff {
         }
         panic!()
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn measures_distances() {
+            let grid = Grid::from_vec(".#...#..#...".chars().collect(), 4);
+            // .#..
+            // .#..
+            // #...
+            let open = |&c: &char| c == '.';
+
+            let distances = distance_map(&grid, [(0, 0)], open);
+            assert_eq!(distances[(1, 0)], Some(1));
+            assert_eq!(distances[(2, 0)], None);
+            assert_eq!(distances[(0, 2)], None);
+
+            let distances = distance_map(&grid, [(0, 0), (2, 3)], open);
+            assert_eq!(distances[(0, 2)], Some(3));
+            assert_eq!(distances[(2, 1)], Some(2));
+        }
+    }
 }
 
 #[allow(unused)]
@@ -240,139 +237,46 @@ pub mod positions_and_directions {
 }
 
 #[allow(unused)]
-pub mod print_helpers {
-    use grid::Grid;
+pub mod print_helpers;
 
-    pub trait ToChar {
-        fn to_char(&self) -> char;
-    }
+#[allow(unused)]
+pub mod color;
 
-    impl ToChar for bool {
-        fn to_char(&self) -> char {
-            match self {
-                true => '#',
-                false => '.',
-            }
-        }
-    }
+#[allow(unused)]
+pub mod math;
 
-    impl ToChar for usize {
-        fn to_char(&self) -> char {
-            match self {
-                0 => '0',
-                1 => '1',
-                2 => '2',
-                3 => '3',
-                4 => '4',
-                5 => '5',
-                6 => '6',
-                7 => '7',
-                8 => '8',
-                9 => '9',
-                _ => '#',
-            }
-        }
-    }
+#[allow(unused)]
+pub mod graph;
 
-    impl<T> ToChar for Option<T>
-    where
-        T: ToChar,
-    {
-        fn to_char(&self) -> char {
-            if let Some(x) = self {
-                x.to_char()
-            } else {
-                '.'
-            }
-        }
-    }
+#[allow(unused)]
+pub mod intervals;
 
-    pub fn print_grid<T: ToChar>(grid: &Grid<T>) {
-        for row in grid.iter_rows() {
-            for item in row {
-                print!("{}", item.to_char())
-            }
-            println!()
-        }
-        println!()
-    }
-}
+#[allow(unused)]
+pub mod numeric;
 
 #[allow(unused)]
-pub mod color {
-
-    pub fn to_hex(char: char) -> u8 {
-        match char {
-            '0' => 0,
-            '1' => 1,
-            '2' => 2,
-            '3' => 3,
-            '4' => 4,
-            '5' => 5,
-            '6' => 6,
-            '7' => 7,
-            '8' => 8,
-            '9' => 9,
-            'a' => 10,
-            'b' => 11,
-            'c' => 12,
-            'd' => 13,
-            'e' => 14,
-            'f' => 15,
-            _ => panic!("not a color"),
-        }
-    }
+pub mod geometry;
 
-    pub fn hex_to_bin(hex: &str) -> usize {
-        hex.chars().fold(0, |z, u| z * 16 + to_hex(u) as usize)
-    }
+#[allow(unused)]
+pub mod trace;
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub struct Color {
-        pub r: u8,
-        pub g: u8,
-        pub b: u8,
-    }
-    impl Color {
-        pub fn from_string(str: &str) -> Self {
-            let mut chars = str.chars();
-            let r1 = to_hex(chars.next().unwrap());
-            let r2 = to_hex(chars.next().unwrap());
-
-            let g1 = to_hex(chars.next().unwrap());
-            let g2 = to_hex(chars.next().unwrap());
-
-            let b1 = to_hex(chars.next().unwrap());
-            let b2 = to_hex(chars.next().unwrap());
-
-            Color {
-                r: (r1 << 4) | r2,
-                g: (g1 << 4) | g2,
-                b: (b1 << 4) | b2,
-            }
-        }
-    }
-}
+#[allow(unused)]
+pub mod scan;
 
-pub mod math {
-    pub fn gcd<T>(mut a: T, mut b: T) -> T
-    where
-        T: std::ops::Rem<Output = T> + Default + std::cmp::PartialEq + Copy,
-    {
-        while b != T::default() {
-            (a, b) = (b, a % b);
-        }
-        a
-    }
-    pub fn lcm<T>(a: T, b: T) -> T
-    where
-        T: std::ops::Rem<Output = T>
-            + std::ops::Div<Output = T>
-            + std::ops::Mul<Output = T>
-            + Default
-            + std::cmp::PartialEq
-            + Copy,
-    {
-        a / gcd(a, b) * b
-    }
-}
+#[allow(unused)]
+pub mod parse;
+
+#[allow(unused)]
+pub mod memo;
+
+#[allow(unused)]
+pub mod cycle;
+
+#[allow(unused)]
+pub mod bitgrid;
+
+#[allow(unused)]
+pub mod space;
+
+#[allow(unused)]
+pub mod aho_corasick;
@@ -0,0 +1,575 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// An integer type `Rational` can be built on, `i128` or `BigInt`.
+pub trait ExactInt:
+    Clone
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+    + From<i64>
+{
+}
+impl<T> ExactInt for T where
+    T: Clone
+        + Ord
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Rem<Output = T>
+        + Neg<Output = T>
+        + From<i64>
+{
+}
+
+fn abs<T: ExactInt>(x: T) -> T {
+    if x < T::from(0) {
+        -x
+    } else {
+        x
+    }
+}
+
+fn gcd<T: ExactInt>(mut a: T, mut b: T) -> T {
+    while b != T::from(0) {
+        let r = a % b.clone();
+        (a, b) = (b, r);
+    }
+    abs(a)
+}
+
+/// An exact fraction, always stored in lowest terms with a positive
+/// denominator.
+///
+/// `Rational` on its own is backed by `i128`, use `Rational<BigInt>` when
+/// the numbers could get bigger than that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational<T = i128> {
+    numer: T,
+    denom: T,
+}
+
+impl<T: ExactInt> Rational<T> {
+    pub fn new(numer: T, denom: T) -> Self {
+        let zero = T::from(0);
+        assert!(denom != zero, "denominator of zero");
+        let (numer, denom) = if denom < zero {
+            (-numer, -denom)
+        } else {
+            (numer, denom)
+        };
+        let g = gcd(numer.clone(), denom.clone());
+        Rational {
+            numer: numer / g.clone(),
+            denom: denom / g,
+        }
+    }
+
+    pub fn from_integer(n: T) -> Self {
+        Rational {
+            numer: n,
+            denom: T::from(1),
+        }
+    }
+
+    pub fn numer(&self) -> &T {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &T {
+        &self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer == T::from(0)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == T::from(1)
+    }
+
+    pub fn to_integer(&self) -> Option<T> {
+        self.is_integer().then(|| self.numer.clone())
+    }
+}
+
+impl<T: ExactInt> From<T> for Rational<T> {
+    fn from(n: T) -> Self {
+        Rational::from_integer(n)
+    }
+}
+
+// the operators cancel common factors before multiplying, which keeps the
+// numbers small, but adding and comparing still cross-multiply before
+// reducing, so an `i128` backed fraction can overflow even when the answer
+// fits. Use `BigInt` if that matters.
+
+impl<T: ExactInt> Add for Rational<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let g = gcd(self.denom.clone(), other.denom.clone());
+        let numer = self.numer * (other.denom.clone() / g.clone())
+            + other.numer * (self.denom.clone() / g.clone());
+        Rational::new(numer, self.denom / g * other.denom)
+    }
+}
+
+impl<T: ExactInt> Sub for Rational<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<T: ExactInt> Mul for Rational<T> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        let g1 = gcd(self.numer.clone(), other.denom.clone());
+        let g2 = gcd(other.numer.clone(), self.denom.clone());
+        Rational::new(
+            (self.numer / g1.clone()) * (other.numer / g2.clone()),
+            (self.denom / g2) * (other.denom / g1),
+        )
+    }
+}
+
+impl<T: ExactInt> Div for Rational<T> {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        assert!(!other.is_zero(), "division by zero");
+        self * Rational::new(other.denom, other.numer)
+    }
+}
+
+impl<T: ExactInt> Neg for Rational<T> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Rational {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl<T: ExactInt> PartialOrd for Rational<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ExactInt> Ord for Rational<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // same sign as self - other, as the denominators are positive
+        let diff = self.clone() - other.clone();
+        diff.numer.cmp(&T::from(0))
+    }
+}
+
+impl<T: ExactInt + Display> Display for Rational<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/// Solves the linear system given as rows of `[a_1, ..., a_n, b]`, with
+/// Gauss-Jordan elimination. Returns `None` if there isn't exactly one
+/// solution.
+pub fn solve_linear_system<T: ExactInt>(
+    mut rows: Vec<Vec<Rational<T>>>,
+) -> Option<Vec<Rational<T>>> {
+    let n = rows.len();
+    assert!(
+        rows.iter().all(|row| row.len() == n + 1),
+        "every row needs n coefficients and a constant"
+    );
+
+    for col in 0..n {
+        let pivot_row = (col..n).find(|&row| !rows[row][col].is_zero())?;
+        rows.swap(col, pivot_row);
+
+        let pivot = rows[col][col].clone();
+        for value in rows[col].iter_mut() {
+            *value = value.clone() / pivot.clone();
+        }
+
+        let pivot_row = rows[col].clone();
+        for (row, values) in rows.iter_mut().enumerate() {
+            if row == col || values[col].is_zero() {
+                continue;
+            }
+            let factor = values[col].clone();
+            for (value, p) in values[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value = value.clone() - factor.clone() * p.clone();
+            }
+        }
+    }
+
+    Some(rows.into_iter().map(|mut row| row.swap_remove(n)).collect())
+}
+
+/// An arbitrarily large integer, for when `i128` isn't enough.
+///
+/// Only has what `Rational` needs, the operators aren't fast.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    // little endian base 2^32 digits, without leading zeros
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let negative = negative && !digits.is_empty();
+        BigInt { negative, digits }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    // the size, if it fits in a u128
+    fn magnitude(&self) -> Option<u128> {
+        if self.digits.len() > 4 {
+            return None;
+        }
+        Some(
+            self.digits
+                .iter()
+                .rev()
+                .fold(0u128, |z, &d| (z << 32) | d as u128),
+        )
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.negative {
+            return None;
+        }
+        self.magnitude()
+    }
+
+    /// The largest `x` with `x * x <= self`. Panics if `self` is negative.
+    pub fn isqrt(&self) -> BigInt {
+        assert!(!self.negative, "square root of a negative number");
+        if let Some(magnitude) = self.magnitude() {
+            return BigInt::from(crate::helpers::math::isqrt(magnitude));
+        }
+
+        // newton's method from a guess that's never too small
+        let bits = self.digits.len() * 32 - self.digits.last().unwrap().leading_zeros() as usize;
+        let mut guess = vec![0u32; bits.div_ceil(2) / 32 + 1];
+        *guess.last_mut().unwrap() = 1 << (bits.div_ceil(2) % 32);
+        let mut x = BigInt::from_parts(false, guess);
+        let two = BigInt::from(2i64);
+        loop {
+            let next = (x.clone() + self.clone() / x.clone()) / two.clone();
+            if next >= x {
+                return x;
+            }
+            x = next;
+        }
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        let magnitude = self.magnitude()?;
+        if !self.negative {
+            i128::try_from(magnitude).ok()
+        } else if magnitude == i128::MIN.unsigned_abs() {
+            Some(i128::MIN)
+        } else {
+            i128::try_from(magnitude).ok().map(|m| -m)
+        }
+    }
+}
+
+fn cmp_digits(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let total = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+// a has to be at least as big as b
+fn sub_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut total = digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+fn mul_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let total = product[i + j] as u64 + x as u64 * y as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+// long division one bit at a time, b can't be zero
+fn divrem_digits(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + the next bit of a
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for digit in remainder.iter_mut() {
+            let next_carry = *digit >> 31;
+            *digit = (*digit << 1) | carry;
+            carry = next_carry;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+
+        if cmp_digits(&remainder, b) != Ordering::Less {
+            remainder = sub_digits(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+fn u128_digits(mut magnitude: u128) -> Vec<u32> {
+    let mut digits = Vec::new();
+    while magnitude > 0 {
+        digits.push(magnitude as u32);
+        magnitude >>= 32;
+    }
+    digits
+}
+
+impl From<i128> for BigInt {
+    fn from(n: i128) -> Self {
+        BigInt::from_parts(n < 0, u128_digits(n.unsigned_abs()))
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(n: u128) -> Self {
+        BigInt::from_parts(false, u128_digits(n))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        BigInt::from(n as i128)
+    }
+}
+
+impl Add for BigInt {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_digits(&self.digits, &other.digits));
+        }
+        match cmp_digits(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_digits(&other.digits, &self.digits))
+            }
+            _ => BigInt::from_parts(self.negative, sub_digits(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for BigInt {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for BigInt {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_digits(&self.digits, &other.digits),
+        )
+    }
+}
+
+/// Rounds towards zero, like the primitive integers.
+impl Div for BigInt {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        assert!(!other.is_zero(), "division by zero");
+        let (quotient, _) = divrem_digits(&self.digits, &other.digits);
+        BigInt::from_parts(self.negative != other.negative, quotient)
+    }
+}
+
+/// Has the sign of `self`, like the primitive integers.
+impl Rem for BigInt {
+    type Output = Self;
+    fn rem(self, other: Self) -> Self {
+        assert!(!other.is_zero(), "division by zero");
+        let (_, remainder) = divrem_digits(&self.digits, &other.digits);
+        BigInt::from_parts(self.negative, remainder)
+    }
+}
+
+impl Neg for BigInt {
+    type Output = Self;
+    fn neg(self) -> Self {
+        BigInt::from_parts(!self.negative, self.digits)
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_digits(&self.digits, &other.digits),
+            (true, true) => cmp_digits(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // split into base 10^9 chunks, smallest first
+        const CHUNK: u32 = 1_000_000_000;
+        let mut chunks = Vec::new();
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let (quotient, remainder) = divrem_digits(&digits, &[CHUNK]);
+            chunks.push(remainder.first().copied().unwrap_or(0));
+            digits = BigInt::from_parts(false, quotient).digits;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn does_fraction_arithmetic() {
+        let half: Rational = Rational::new(1, 2);
+        let third = Rational::new(-2, -6);
+
+        assert_eq!(half + third, Rational::new(5, 6));
+        assert_eq!(half - third, Rational::new(1, 6));
+        assert_eq!(half * third, Rational::new(1, 6));
+        assert_eq!(half / third, Rational::new(3, 2));
+        assert!(third < half && -half < third);
+        assert_eq!(Rational::<i128>::new(4, -2).to_integer(), Some(-2));
+        assert_eq!(format!("{} {}", half, Rational::from(7i128)), "1/2 7");
+
+        // would overflow without cancelling first
+        let big = Rational::new(i128::MAX, 3);
+        assert_eq!(big * Rational::new(3, i128::MAX), Rational::from(1));
+    }
+
+    #[test]
+    fn does_big_integer_arithmetic() {
+        let values: [i128; 7] = [0, 1, -7, 12345, -(1 << 40) + 3, i64::MAX as i128, -99999];
+        for &a in values.iter() {
+            for &b in values.iter() {
+                let (x, y) = (BigInt::from(a), BigInt::from(b));
+                assert_eq!((x.clone() + y.clone()).to_i128(), Some(a + b));
+                assert_eq!((x.clone() - y.clone()).to_i128(), Some(a - b));
+                assert_eq!((x.clone() * y.clone()).to_i128(), Some(a * b));
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+                if b != 0 {
+                    assert_eq!((x.clone() / y.clone()).to_i128(), Some(a / b));
+                    assert_eq!((x % y).to_i128(), Some(a % b));
+                }
+            }
+        }
+
+        let huge = BigInt::from(i128::MAX) * BigInt::from(-1_000_000_000_000i64);
+        assert_eq!(huge.to_i128(), None);
+        assert_eq!(
+            huge.to_string(),
+            "-170141183460469231731687303715884105727000000000000"
+        );
+        assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!(BigInt::from(u128::MAX).to_u128(), Some(u128::MAX));
+        assert_eq!(BigInt::from(-1i64).to_u128(), None);
+    }
+
+    #[test]
+    fn takes_big_square_roots() {
+        let max = BigInt::from(u128::MAX);
+        assert_eq!((max.clone() * max.clone()).isqrt(), max);
+        let below = max.clone() * max.clone() - BigInt::from(1i64);
+        assert_eq!(below.isqrt(), max - BigInt::from(1i64));
+        assert_eq!(BigInt::from(99i64).isqrt(), BigInt::from(9i64));
+        assert_eq!(BigInt::from(1u128 << 64).isqrt(), BigInt::from(1u128 << 32));
+    }
+
+    #[test]
+    fn solves_linear_systems() {
+        let r = |n| Rational::<BigInt>::from(BigInt::from(n as i64));
+        // x + 2y = 5, 3x - y = 1
+        let rows = vec![vec![r(1), r(2), r(5)], vec![r(3), r(-1), r(1)]];
+        assert_eq!(solve_linear_system(rows), Some(vec![r(1), r(2)]));
+
+        let singular: Vec<Vec<Rational>> = vec![
+            vec![Rational::from(1), Rational::from(2), Rational::from(3)],
+            vec![Rational::from(2), Rational::from(4), Rational::from(6)],
+        ];
+        assert_eq!(solve_linear_system(singular), None);
+    }
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+
+use super::scan::ScanInt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// something else was there, says what should have been
+    Expected(String),
+    /// a number too big for its type
+    Overflow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// starting from 1, `None` if the text wasn't parsed line by line
+    pub line: Option<usize>,
+    /// starting from 1, counted in chars
+    pub column: usize,
+    /// the start of what was there instead
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}, ")?;
+        }
+        write!(f, "column {}: ", self.column)?;
+        match &self.kind {
+            ParseErrorKind::Expected(expected) => write!(f, "expected {expected}")?,
+            ParseErrorKind::Overflow => write!(f, "number is too big")?,
+        }
+        if self.found.is_empty() {
+            write!(f, ", found the end")
+        } else {
+            write!(f, ", found {:?}", self.found)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Text being parsed from the front. Each method takes something off the
+/// front, or leaves it alone and returns an error saying what it wanted.
+#[derive(Debug, Clone, Copy)]
+pub struct Input<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Input { text, pos: 0 }
+    }
+
+    /// what's left to parse
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.text.len()
+    }
+
+    /// An error at the current position, for checks the methods here can't
+    /// do.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        self.error_kind(ParseErrorKind::Expected(expected.into()))
+    }
+
+    fn error_kind(&self, kind: ParseErrorKind) -> ParseError {
+        const FOUND_LENGTH: usize = 10;
+        ParseError {
+            kind,
+            line: None,
+            column: self.text[..self.pos].chars().count() + 1,
+            found: self.rest().chars().take(FOUND_LENGTH).collect(),
+        }
+    }
+
+    /// Takes `literal` if it's next.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.pos += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn literal(&mut self, literal: &str) -> Result<(), ParseError> {
+        if self.eat(literal) {
+            Ok(())
+        } else {
+            Err(self.error(format!("{literal:?}")))
+        }
+    }
+
+    /// skips spaces and tabs
+    pub fn spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    }
+
+    /// A whole number, with a `-` in front if `T` is signed.
+    pub fn number<T: ScanInt>(&mut self) -> Result<T, ParseError> {
+        let bytes = self.rest().as_bytes();
+        let negative = T::SIGNED && bytes.first() == Some(&b'-');
+        let digits = &bytes[negative as usize..];
+        let length = digits.iter().take_while(|b| b.is_ascii_digit()).count();
+        if length == 0 {
+            return Err(self.error("a number"));
+        }
+
+        let mut value = T::ZERO;
+        for &b in &digits[..length] {
+            value = value
+                .push_digit(b - b'0', negative)
+                .ok_or_else(|| self.error_kind(ParseErrorKind::Overflow))?;
+        }
+        self.pos += negative as usize + length;
+        Ok(value)
+    }
+
+    /// A name made of letters, digits and `_`.
+    pub fn ident(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("a name"));
+        }
+        self.pos += length;
+        Ok(&rest[..length])
+    }
+
+    /// Takes the next char if it is one of `chars`.
+    pub fn one_of(&mut self, chars: &str) -> Result<char, ParseError> {
+        match self.rest().chars().next() {
+            Some(c) if chars.contains(c) => {
+                self.pos += c.len_utf8();
+                Ok(c)
+            }
+            _ => Err(self.error(format!("one of {chars:?}"))),
+        }
+    }
+
+    /// One or more `item`s with `separator` between them.
+    pub fn separated<T, F>(&mut self, separator: &str, mut item: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&mut Self) -> Result<T, ParseError>,
+    {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("the end"))
+        }
+    }
+}
+
+/// Parses all of `text` with `parser`, it's an error if anything is left.
+pub fn parse_all<'a, T, F>(text: &'a str, parser: F) -> Result<T, ParseError>
+where
+    F: FnOnce(&mut Input<'a>) -> Result<T, ParseError>,
+{
+    let mut input = Input::new(text);
+    let value = parser(&mut input)?;
+    input.end()?;
+    Ok(value)
+}
+
+/// Parses every line of `text` with `parser`, errors say which line.
+pub fn parse_lines<'a, T, F>(text: &'a str, parser: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&mut Input<'a>) -> Result<T, ParseError>,
+{
+    Section {
+        first_line: 1,
+        text,
+    }
+    .parse_lines(parser)
+}
+
+/// A block of lines, with blank lines before and after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// the line number in the whole file, starting from 1
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    pub fn lines(&self) -> std::str::Lines<'a> {
+        self.text.lines()
+    }
+
+    /// Like `parse_lines`, with line numbers counted from the whole file.
+    pub fn parse_lines<T, F>(&self, mut parser: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&mut Input<'a>) -> Result<T, ParseError>,
+    {
+        self.text
+            .lines()
+            .enumerate()
+            .map(|(k, line)| {
+                parse_all(line, &mut parser).map_err(|e| ParseError {
+                    line: Some(self.first_line + k),
+                    ..e
+                })
+            })
+            .collect()
+    }
+}
+
+/// Splits a file on blank lines.
+pub fn sections(file: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![];
+    // (first line number, byte offset) of the section being read
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (k, line) in file.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+        match (start, blank) {
+            (None, false) => start = Some((k + 1, offset)),
+            (Some((first_line, from)), true) => {
+                sections.push(Section {
+                    first_line,
+                    text: file[from..offset].trim_end_matches(['\n', '\r']),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((first_line, from)) = start {
+        sections.push(Section {
+            first_line,
+            text: file[from..].trim_end_matches(['\n', '\r']),
+        });
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pieces() {
+        let mut input = Input::new("Game -12: ab_3, x;");
+        input.literal("Game").unwrap();
+        input.spaces();
+        assert_eq!(input.number::<i32>(), Ok(-12));
+        input.literal(": ").unwrap();
+        assert_eq!(input.separated(", ", |i| i.ident()), Ok(vec!["ab_3", "x"]));
+        assert_eq!(input.one_of(";,"), Ok(';'));
+        assert!(input.end().is_ok());
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut input = Input::new("12 apples");
+        assert_eq!(input.number::<u8>(), Ok(12));
+        let error = input.literal(", ").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::Expected("\", \"".to_string()));
+        assert_eq!(error.column, 3);
+        assert_eq!(error.found, " apples");
+
+        // nothing is taken when it fails
+        assert_eq!(input.rest(), " apples");
+        assert!(Input::new("-1").number::<u32>().is_err());
+        assert_eq!(
+            Input::new("300").number::<u8>().unwrap_err().kind,
+            ParseErrorKind::Overflow
+        );
+
+        let error = parse_lines("1,2\n3,x", |i| i.separated(",", |i| i.number::<u8>()));
+        let error = error.unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), 3));
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 3: expected a number, found \"x\""
+        );
+    }
+
+    #[test]
+    fn splits_sections() {
+        let file = "a\nb\n\n\nc\r\n\r\nd\n";
+        let found: Vec<(usize, &str)> = sections(file)
+            .iter()
+            .map(|s| (s.first_line, s.text))
+            .collect();
+        assert_eq!(found, vec![(1, "a\nb"), (5, "c"), (7, "d")]);
+
+        let error = sections(file)[2].parse_lines(|i| i.number::<u8>());
+        assert_eq!(error.unwrap_err().line, Some(7));
+    }
+}
@@ -0,0 +1,396 @@
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use grid::Grid;
+
+use super::color::Color;
+use super::enums_and_types::Position;
+
+pub trait ToChar {
+    fn to_char(&self) -> char;
+}
+
+impl ToChar for bool {
+    fn to_char(&self) -> char {
+        match self {
+            true => '#',
+            false => '.',
+        }
+    }
+}
+
+/// `0` to `9`, then `a` to `z` and `A` to `Z`, so up to 61 fits in a char.
+/// Anything bigger is `#`, use a `Canvas::heatmap` for those.
+impl ToChar for usize {
+    fn to_char(&self) -> char {
+        const SCALE: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        SCALE.get(*self).map_or('#', |&c| c as char)
+    }
+}
+
+impl<T> ToChar for Option<T>
+where
+    T: ToChar,
+{
+    fn to_char(&self) -> char {
+        if let Some(x) = self {
+            x.to_char()
+        } else {
+            '.'
+        }
+    }
+}
+
+pub fn print_grid<T: ToChar>(grid: &Grid<T>) {
+    for row in grid.iter_rows() {
+        for item in row {
+            print!("{}", item.to_char())
+        }
+        println!()
+    }
+    println!()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub c: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            c: ' ',
+            fg: None,
+            bg: None,
+        }
+    }
+}
+impl Cell {
+    /// the colour of the cell when drawn as a pixel
+    fn pixel(&self) -> Color {
+        const EMPTY: Color = Color { r: 0, g: 0, b: 0 };
+        const FILLED: Color = Color {
+            r: 200,
+            g: 200,
+            b: 200,
+        };
+        self.fg.or(self.bg).unwrap_or(match self.c {
+            '.' | ' ' => EMPTY,
+            _ => FILLED,
+        })
+    }
+}
+
+/// A grid of characters with colours, for drawing a grid with paths and
+/// values on top of it, to the terminal or to an image.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    cells: Grid<Cell>,
+}
+
+impl Canvas {
+    pub fn from_grid<T: ToChar>(grid: &Grid<T>) -> Self {
+        let mut cells: Grid<Cell> = Grid::new(grid.rows(), grid.cols());
+        for ((j, i), item) in grid.indexed_iter() {
+            cells[(j, i)].c = item.to_char();
+        }
+        Canvas { cells }
+    }
+
+    /// Like `from_grid`, for grids kept as a `Vec` of rows.
+    pub fn from_rows<T: ToChar>(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let mut cells: Grid<Cell> = Grid::new(rows.len(), cols);
+        for (j, row) in rows.iter().enumerate() {
+            for (i, item) in row.iter().enumerate() {
+                cells[(j, i)].c = item.to_char();
+            }
+        }
+        Canvas { cells }
+    }
+
+    /// Colours each cell by where its value is between the smallest and
+    /// largest values in the grid, from blue up to red. Cells without a
+    /// value are left as `.`.
+    pub fn heatmap<T, F>(grid: &Grid<T>, value: F) -> Self
+    where
+        F: Fn(&T) -> Option<f64>,
+    {
+        let values: Vec<Option<f64>> = grid.iter().map(value).collect();
+        let (min, max) = values
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                (lo.min(v), hi.max(v))
+            });
+
+        let mut cells: Grid<Cell> = Grid::new(grid.rows(), grid.cols());
+        for (cell, v) in cells.iter_mut().zip(values) {
+            *cell = match v {
+                Some(v) => Cell {
+                    c: ' ',
+                    fg: None,
+                    bg: Some(heat_color(if max > min {
+                        (v - min) / (max - min)
+                    } else {
+                        0.0
+                    })),
+                },
+                None => Cell {
+                    c: '.',
+                    ..Cell::default()
+                },
+            }
+        }
+        Canvas { cells }
+    }
+
+    pub fn cells(&self) -> &Grid<Cell> {
+        &self.cells
+    }
+
+    /// Sets the background of the cells, positions outside the grid are
+    /// ignored.
+    pub fn highlight<I>(&mut self, positions: I, color: Color) -> &mut Self
+    where
+        I: IntoIterator<Item = Position>,
+    {
+        for pos in positions {
+            if let Some(cell) = self.cells.get_mut(pos.0, pos.1) {
+                cell.bg = Some(color);
+            }
+        }
+        self
+    }
+
+    /// Draws `c` over the cells in `color`, like a path on top of a heatmap.
+    pub fn mark<I>(&mut self, positions: I, c: char, color: Color) -> &mut Self
+    where
+        I: IntoIterator<Item = Position>,
+    {
+        for pos in positions {
+            if let Some(cell) = self.cells.get_mut(pos.0, pos.1) {
+                cell.c = c;
+                cell.fg = Some(color);
+            }
+        }
+        self
+    }
+
+    /// The canvas with 24 bit ANSI colour codes, one line per row.
+    pub fn to_ansi_string(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.iter_rows() {
+            for cell in row {
+                if let Some(Color { r, g, b }) = cell.fg {
+                    write!(out, "\x1b[38;2;{r};{g};{b}m").unwrap();
+                }
+                if let Some(Color { r, g, b }) = cell.bg {
+                    write!(out, "\x1b[48;2;{r};{g};{b}m").unwrap();
+                }
+                out.push(cell.c);
+                if cell.fg.is_some() || cell.bg.is_some() {
+                    out.push_str("\x1b[0m");
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn print(&self) {
+        println!("{}", self.to_ansi_string())
+    }
+
+    /// Every pixel, row by row, with each cell as a `scale` by `scale`
+    /// square.
+    pub fn pixels(&self, scale: usize) -> Vec<Color> {
+        self.pixel_rows(scale).flatten().collect()
+    }
+
+    // each cell becomes a `scale` by `scale` square of pixels
+    fn pixel_rows(&self, scale: usize) -> impl Iterator<Item = Vec<Color>> + '_ {
+        (0..self.cells.rows() * scale).map(move |y| {
+            (0..self.cells.cols() * scale)
+                .map(|x| self.cells[(y / scale, x / scale)].pixel())
+                .collect()
+        })
+    }
+
+    /// The canvas as a binary PPM image.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.cells.cols() * scale, self.cells.rows() * scale);
+        let mut ppm = format!("P6\n{width} {height}\n255\n").into_bytes();
+        for row in self.pixel_rows(scale) {
+            ppm.extend(rgb_bytes(&row));
+        }
+        ppm
+    }
+
+    /// The canvas as a PNG image. The pixels aren't compressed, which keeps
+    /// the encoder small, so the files are about as big as a PPM.
+    pub fn to_png(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.cells.cols() * scale, self.cells.rows() * scale);
+
+        // every row starts with filter type 0 (none)
+        let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+        for row in self.pixel_rows(scale) {
+            raw.push(0);
+            raw.extend(rgb_bytes(&row));
+        }
+
+        let mut header = Vec::new();
+        header.extend((width as u32).to_be_bytes());
+        header.extend((height as u32).to_be_bytes());
+        // 8 bits per channel, rgb, default compression, filter and interlace
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    pub fn write_ppm<P: AsRef<Path>>(&self, path: P, scale: usize) -> io::Result<()> {
+        std::fs::write(path, self.to_ppm(scale))
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, path: P, scale: usize) -> io::Result<()> {
+        std::fs::write(path, self.to_png(scale))
+    }
+}
+
+/// A colour from blue (0.0) through green and yellow to red (1.0).
+pub fn heat_color(t: f64) -> Color {
+    const STOPS: [(u8, u8, u8); 5] = [
+        (0, 0, 255),
+        (0, 255, 255),
+        (0, 255, 0),
+        (255, 255, 0),
+        (255, 0, 0),
+    ];
+    let t = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let k = (t as usize).min(STOPS.len() - 2);
+    let f = t - k as f64;
+
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+    let ((r1, g1, b1), (r2, g2, b2)) = (STOPS[k], STOPS[k + 1]);
+    Color {
+        r: mix(r1, r2),
+        g: mix(g1, g2),
+        b: mix(b1, b2),
+    }
+}
+
+fn rgb_bytes(row: &[Color]) -> impl Iterator<Item = u8> + '_ {
+    row.iter().flat_map(|&Color { r, g, b }| [r, g, b])
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut out = vec![0x78, 0x01];
+
+    let blocks: Vec<&[u8]> = if data.is_empty() {
+        vec![&[]]
+    } else {
+        data.chunks(MAX_BLOCK).collect()
+    };
+    for (k, block) in blocks.iter().enumerate() {
+        let is_last = k == blocks.len() - 1;
+        out.push(is_last as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(*block);
+    }
+
+    // adler-32 of the uncompressed data
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+
+    #[test]
+    fn renders_ansi() {
+        let grid = Grid::from_vec(vec![true, false, false, true], 2);
+        let mut canvas = Canvas::from_grid(&grid);
+        canvas.highlight([(0, 1)], RED);
+        assert_eq!(canvas.to_ansi_string(), "#\x1b[48;2;255;0;0m.\x1b[0m\n.#\n");
+    }
+
+    #[test]
+    fn writes_numbers_as_chars() {
+        let chars: String = [0usize, 9, 10, 35, 36, 61, 62, 1000]
+            .iter()
+            .map(|n| n.to_char())
+            .collect();
+        assert_eq!(chars, "09azAZ##");
+    }
+
+    #[test]
+    fn renders_heatmaps() {
+        let grid = Grid::from_vec(vec![Some(1), Some(3), None, Some(5)], 2);
+        let canvas = Canvas::heatmap(&grid, |v| v.map(|v| v as f64));
+        assert_eq!(canvas.cells()[(0, 0)].bg, Some(heat_color(0.0)));
+        assert_eq!(canvas.cells()[(0, 1)].bg, Some(heat_color(0.5)));
+        assert_eq!(canvas.cells()[(1, 0)].bg, None);
+        assert_eq!(heat_color(1.0), RED);
+        assert_eq!(heat_color(0.5), Color { r: 0, g: 255, b: 0 });
+    }
+
+    #[test]
+    fn writes_images() {
+        let grid = Grid::from_vec(vec![true, false], 2);
+        let mut canvas = Canvas::from_grid(&grid);
+        canvas.mark([(0, 1)], '*', RED);
+
+        let ppm = canvas.to_ppm(1);
+        assert_eq!(ppm, b"P6\n2 1\n255\n\xc8\xc8\xc8\xff\x00\x00");
+
+        let png = canvas.to_png(2);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 4, 0, 0, 0, 2]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        // the crc of an empty IEND chunk is well known
+        assert_eq!(&png[png.len() - 4..], &[0xae, 0x42, 0x60, 0x82]);
+    }
+}
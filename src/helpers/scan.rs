@@ -0,0 +1,245 @@
+/// An integer type the scanner can build digit by digit.
+pub trait ScanInt: Copy {
+    const SIGNED: bool;
+    const ZERO: Self;
+
+    /// `self * 10 + digit` going away from zero, `None` on overflow
+    fn push_digit(self, digit: u8, negative: bool) -> Option<Self>;
+}
+
+macro_rules! scan_int {
+    ($signed:expr, $($t:ty),*) => {$(
+        impl ScanInt for $t {
+            const SIGNED: bool = $signed;
+            const ZERO: Self = 0;
+
+            fn push_digit(self, digit: u8, negative: bool) -> Option<Self> {
+                let shifted = self.checked_mul(10)?;
+                // building negative numbers downwards reaches MIN
+                if negative {
+                    shifted.checked_sub(digit as Self)
+                } else {
+                    shifted.checked_add(digit as Self)
+                }
+            }
+        }
+    )*};
+}
+scan_int!(true, i8, i16, i32, i64, i128, isize);
+scan_int!(false, u8, u16, u32, u64, u128, usize);
+
+/// Iterator over the integers in a string, without allocating.
+///
+/// By default any byte that isn't part of a number is skipped, like
+/// `-?\d+`. A `-` right before a digit makes it negative, for signed types
+/// only. With `separated_by`, only tokens between separators that are whole
+/// numbers count, so `11A` or `x1` are skipped.
+///
+/// Panics if a number doesn't fit in `T`, see `checked` to handle that.
+#[derive(Debug, Clone)]
+pub struct Numbers<'a, T> {
+    bytes: &'a [u8],
+    pos: usize,
+    separators: Option<&'a [u8]>,
+    // read a `-` for unsigned types too, so the number doesn't fit
+    keep_sign: bool,
+    _type: std::marker::PhantomData<T>,
+}
+
+/// The numbers from `Numbers::checked`.
+#[derive(Debug, Clone)]
+pub struct Checked<'a, T>(Numbers<'a, T>);
+
+pub fn numbers<T: ScanInt>(line: &str) -> Numbers<'_, T> {
+    Numbers {
+        bytes: line.as_bytes(),
+        pos: 0,
+        separators: None,
+        keep_sign: false,
+        _type: std::marker::PhantomData,
+    }
+}
+
+impl<'a, T: ScanInt> Numbers<'a, T> {
+    /// Splits into tokens on these bytes, and only yields tokens that are
+    /// numbers.
+    pub fn separated_by(mut self, separators: &'a [u8]) -> Self {
+        self.separators = Some(separators);
+        self
+    }
+
+    /// Yields `None` for a number that doesn't fit in `T` instead of
+    /// panicking. For unsigned types a `-` in front is kept, so a negative
+    /// number is `None` too, where otherwise the sign is skipped.
+    pub fn checked(mut self) -> Checked<'a, T> {
+        self.keep_sign = true;
+        Checked(self)
+    }
+
+    // reads a number starting at `start`, returning it, `None` if it
+    // doesn't fit, and where it ends
+    fn read_number(&self, start: usize) -> Option<(Option<T>, usize)> {
+        let mut k = start;
+        let minus = (T::SIGNED || self.keep_sign) && self.bytes.get(k) == Some(&b'-');
+        if minus {
+            k += 1;
+        }
+        if !self.bytes.get(k).is_some_and(u8::is_ascii_digit) {
+            return None;
+        }
+
+        let mut value = (T::SIGNED || !minus).then_some(T::ZERO);
+        while let Some(&b) = self.bytes.get(k).filter(|b| b.is_ascii_digit()) {
+            value = value.and_then(|value| value.push_digit(b - b'0', minus));
+            k += 1;
+        }
+        Some((value, k))
+    }
+
+    fn next_checked(&mut self) -> Option<Option<T>> {
+        match self.separators {
+            None => self.next_anywhere(),
+            Some(separators) => self.next_separated(separators),
+        }
+    }
+
+    fn next_anywhere(&mut self) -> Option<Option<T>> {
+        while self.pos < self.bytes.len() {
+            if let Some((value, end)) = self.read_number(self.pos) {
+                self.pos = end;
+                return Some(value);
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    fn next_separated(&mut self, separators: &[u8]) -> Option<Option<T>> {
+        while self.pos < self.bytes.len() {
+            let start = self.pos;
+            let end = self.bytes[start..]
+                .iter()
+                .position(|b| separators.contains(b))
+                .map_or(self.bytes.len(), |k| start + k);
+            // skip the separator too
+            self.pos = end + 1;
+
+            match self.read_number(start) {
+                Some((value, number_end)) if number_end == end => return Some(value),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: ScanInt> Iterator for Numbers<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.next_checked()
+            .map(|value| value.expect("number doesn't fit in the integer type"))
+    }
+}
+
+impl<'a, T: ScanInt> Iterator for Checked<'a, T> {
+    type Item = Option<T>;
+
+    fn next(&mut self) -> Option<Option<T>> {
+        self.0.next_checked()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_numbers_anywhere() {
+        let found: Vec<i32> = numbers("seeds: 79 14, -55 x13 a-7-").collect();
+        assert_eq!(found, vec![79, 14, -55, 13, -7]);
+
+        // no signs for unsigned types
+        let found: Vec<u64> = numbers("10 -20 --3").collect();
+        assert_eq!(found, vec![10, 20, 3]);
+
+        assert_eq!(numbers::<u8>("").count(), 0);
+        assert_eq!(numbers::<u8>("no numbers - here").count(), 0);
+    }
+
+    #[test]
+    fn reaches_the_limits() {
+        let found: Vec<i8> = numbers("-128 127 -0").collect();
+        assert_eq!(found, vec![-128, 127, 0]);
+        let found: Vec<u128> = numbers(&u128::MAX.to_string()).collect();
+        assert_eq!(found, vec![u128::MAX]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_on_overflow() {
+        numbers::<u8>("256").for_each(drop);
+    }
+
+    #[test]
+    fn checks_what_fits() {
+        let found: Vec<Option<u8>> = numbers("12 -20 256 -0 x7").checked().collect();
+        assert_eq!(found, vec![Some(12), None, None, None, Some(7)]);
+        let found: Vec<Option<i8>> = numbers("-128 -129 --5").checked().collect();
+        assert_eq!(found, vec![Some(-128), None, Some(-5)]);
+
+        // what the regex version gave
+        let found: Vec<u64> =
+            crate::helpers::constructor::line_to_digits("5 -20 99999999999999999999");
+        assert_eq!(found, vec![5, 0, 0]);
+    }
+
+    #[test]
+    fn splits_on_separators() {
+        let found: Vec<i64> = numbers("1,-2,,3 11A x1 4").separated_by(b", ").collect();
+        assert_eq!(found, vec![1, -2, 3, 4]);
+
+        let found: Vec<u8> = numbers("1,1,3").separated_by(b",").collect();
+        assert_eq!(found, vec![1, 1, 3]);
+    }
+
+    // cargo test --release -- --ignored --nocapture compares_with_regex
+    // both sides collect each line, like line_to_digits does
+    #[test]
+    #[ignore = "benchmark"]
+    fn compares_with_regex() {
+        use regex::Regex;
+        use std::time::Instant;
+
+        let line = (0..1000)
+            .map(|k: i64| (k * 7919 - 3_000_000).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let file = vec![line; 2000].join("\n");
+
+        let start = Instant::now();
+        let re = Regex::new(r"(-?\d+)").unwrap();
+        let with_regex: i64 = file
+            .lines()
+            .map(|line| {
+                re.find_iter(line)
+                    .map(|m| m.as_str().parse::<i64>().unwrap())
+                    .collect::<Vec<_>>()
+                    .iter()
+                    .sum::<i64>()
+            })
+            .sum();
+        let regex_time = start.elapsed();
+
+        let start = Instant::now();
+        let with_scanner: i64 = file
+            .lines()
+            .map(|line| numbers::<i64>(line).collect::<Vec<_>>().iter().sum::<i64>())
+            .sum();
+        let scanner_time = start.elapsed();
+
+        assert_eq!(with_regex, with_scanner);
+        println!("regex:   {:?}", regex_time);
+        println!("scanner: {:?}", scanner_time);
+    }
+}
@@ -0,0 +1,354 @@
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+/// A place in 3D space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// A movement in 3D space, like a velocity or the gap between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Copy> Point3<T> {
+    pub fn to_array(self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl<T> Vec3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl<T: Copy> Vec3<T> {
+    pub fn to_array(self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Vec3<T> {
+    pub fn dot(self, other: Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+}
+
+impl<T: Add<Output = T>> Add<Vec3<T>> for Point3<T> {
+    type Output = Point3<T>;
+    fn add(self, v: Vec3<T>) -> Point3<T> {
+        Point3::new(self.x + v.x, self.y + v.y, self.z + v.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub<Vec3<T>> for Point3<T> {
+    type Output = Point3<T>;
+    fn sub(self, v: Vec3<T>) -> Point3<T> {
+        Point3::new(self.x - v.x, self.y - v.y, self.z - v.z)
+    }
+}
+
+/// the vector from `other` to `self`
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Vec3<T>;
+    fn sub(self, other: Point3<T>) -> Vec3<T> {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vec3<T> {
+    type Output = Vec3<T>;
+    fn add(self, other: Vec3<T>) -> Vec3<T> {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vec3<T> {
+    type Output = Vec3<T>;
+    fn sub(self, other: Vec3<T>) -> Vec3<T> {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vec3<T> {
+    type Output = Vec3<T>;
+    fn neg(self) -> Vec3<T> {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// scaling by a number
+impl<T: Copy + Mul<Output = T>> Mul<T> for Vec3<T> {
+    type Output = Vec3<T>;
+    fn mul(self, k: T) -> Vec3<T> {
+        Vec3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+/// An axis aligned box, including both corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Box3<T> {
+    pub min: Point3<T>,
+    pub max: Point3<T>,
+}
+
+impl<T: Copy + Ord> Box3<T> {
+    /// The box with `a` and `b` at opposite corners, in any order.
+    pub fn new(a: Point3<T>, b: Point3<T>) -> Self {
+        Box3 {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// The smallest box around all the points, `None` if there are none.
+    pub fn bounding<I: IntoIterator<Item = Point3<T>>>(points: I) -> Option<Self> {
+        points.into_iter().fold(None, |bounds, p| {
+            Some(match bounds {
+                None => Box3 { min: p, max: p },
+                Some(bounds) => bounds.union(&Box3 { min: p, max: p }),
+            })
+        })
+    }
+
+    /// The smallest box around both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        Box3::new(
+            Point3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            Point3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        )
+    }
+
+    pub fn contains(&self, p: Point3<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    /// The part inside both boxes, `None` if they don't touch.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = Point3::new(
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.min.z.max(other.min.z),
+        );
+        let max = Point3::new(
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+            self.max.z.min(other.max.z),
+        );
+        (min.x <= max.x && min.y <= max.y && min.z <= max.z).then_some(Box3 { min, max })
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Add<Vec3<T>> for Box3<T> {
+    type Output = Box3<T>;
+    fn add(self, v: Vec3<T>) -> Box3<T> {
+        Box3 {
+            min: self.min + v,
+            max: self.max + v,
+        }
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> Sub<Vec3<T>> for Box3<T> {
+    type Output = Box3<T>;
+    fn sub(self, v: Vec3<T>) -> Box3<T> {
+        Box3 {
+            min: self.min - v,
+            max: self.max - v,
+        }
+    }
+}
+
+impl Box3<usize> {
+    /// how many cells are in the box
+    pub fn volume(&self) -> usize {
+        (self.max.x - self.min.x + 1)
+            * (self.max.y - self.min.y + 1)
+            * (self.max.z - self.min.z + 1)
+    }
+
+    /// every cell in the box, x changing fastest
+    pub fn points(self) -> impl Iterator<Item = Point3<usize>> {
+        (self.min.z..=self.max.z).flat_map(move |z| {
+            (self.min.y..=self.max.y)
+                .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Point3::new(x, y, z)))
+        })
+    }
+}
+
+/// A 3D grid of cells from `(0, 0, 0)` up to but not including `size`,
+/// stored one z layer after another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoxelGrid<T> {
+    size: Point3<usize>,
+    cells: Vec<T>,
+}
+
+impl<T: Clone + Default> VoxelGrid<T> {
+    pub fn new(x: usize, y: usize, z: usize) -> Self {
+        VoxelGrid {
+            size: Point3::new(x, y, z),
+            cells: vec![T::default(); x * y * z],
+        }
+    }
+}
+
+impl<T> VoxelGrid<T> {
+    pub fn size(&self) -> Point3<usize> {
+        self.size
+    }
+
+    /// the box covering every cell
+    pub fn bounds(&self) -> Option<Box3<usize>> {
+        let Point3 { x, y, z } = self.size;
+        (x > 0 && y > 0 && z > 0).then(|| Box3 {
+            min: Point3::new(0, 0, 0),
+            max: Point3::new(x - 1, y - 1, z - 1),
+        })
+    }
+
+    fn offset(&self, p: Point3<usize>) -> Option<usize> {
+        let size = self.size;
+        (p.x < size.x && p.y < size.y && p.z < size.z)
+            .then_some((p.z * size.y + p.y) * size.x + p.x)
+    }
+
+    /// `None` if the point is outside the grid
+    pub fn get(&self, p: Point3<usize>) -> Option<&T> {
+        self.offset(p).map(|k| &self.cells[k])
+    }
+
+    pub fn get_mut(&mut self, p: Point3<usize>) -> Option<&mut T> {
+        self.offset(p).map(|k| &mut self.cells[k])
+    }
+
+    /// The cells of one z layer, a row of x for each y.
+    pub fn layer(&self, z: usize) -> &[T] {
+        let layer = self.size.x * self.size.y;
+        &self.cells[z * layer..(z + 1) * layer]
+    }
+
+    /// the cells inside `area`, which must be inside the grid
+    pub fn iter_box(&self, area: Box3<usize>) -> impl Iterator<Item = &T> + '_ {
+        area.points().map(|p| &self[p])
+    }
+}
+
+impl<T: Clone> VoxelGrid<T> {
+    /// Sets every cell of `area`, which must be inside the grid.
+    pub fn fill_box(&mut self, area: Box3<usize>, value: T) {
+        for p in area.points() {
+            self[p] = value.clone();
+        }
+    }
+}
+
+/// Panics if the point is outside the grid.
+impl<T> Index<Point3<usize>> for VoxelGrid<T> {
+    type Output = T;
+    fn index(&self, p: Point3<usize>) -> &T {
+        self.get(p).expect("point outside the grid")
+    }
+}
+
+impl<T> IndexMut<Point3<usize>> for VoxelGrid<T> {
+    fn index_mut(&mut self, p: Point3<usize>) -> &mut T {
+        self.get_mut(p).expect("point outside the grid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn does_vector_arithmetic() {
+        let p = Point3::new(1, 2, 3);
+        let v = Vec3::new(-1, 0, 2);
+        assert_eq!(p + v * 2, Point3::new(-1, 2, 7));
+        assert_eq!(Point3::new(4, 4, 4) - p, Vec3::new(3, 2, 1));
+        assert_eq!(-v, Vec3::new(1, 0, -2));
+        assert_eq!(v.dot(Vec3::new(3, 5, 7)), 11);
+
+        let (x, y) = (Vec3::new(1, 0, 0), Vec3::new(0, 1, 0));
+        assert_eq!(x.cross(y), Vec3::new(0, 0, 1));
+        assert_eq!(y.cross(x), Vec3::new(0, 0, -1));
+    }
+
+    #[test]
+    fn intersects_boxes() {
+        let a = Box3::new(Point3::new(2, 0, 0), Point3::new(0, 2, 2));
+        assert_eq!(a.min, Point3::new(0, 0, 0));
+        let b = Box3::new(Point3::new(1, 1, 2), Point3::new(5, 1, 9));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Box3::new(Point3::new(1, 1, 2), Point3::new(2, 1, 2)))
+        );
+        // touching corners count, since both corners are inside a box
+        let c = Box3::new(Point3::new(2, 2, 2), Point3::new(3, 3, 3));
+        assert!(a.intersects(&c));
+        assert!(!(c + Vec3::new(0, 0, 1)).intersects(&a));
+
+        let bounds = Box3::bounding([Point3::new(3, -1, 0), Point3::new(0, 4, 2)]);
+        assert_eq!(
+            bounds,
+            Some(Box3::new(Point3::new(0, -1, 0), Point3::new(3, 4, 2)))
+        );
+        assert!(bounds.unwrap().contains(Point3::new(1, 0, 1)));
+        assert_eq!(Box3::<i32>::bounding([]), None);
+    }
+
+    #[test]
+    fn indexes_voxels() {
+        let mut voxels: VoxelGrid<Option<u8>> = VoxelGrid::new(3, 2, 4);
+        let area = Box3::new(Point3::new(1, 0, 1), Point3::new(2, 1, 1));
+        assert_eq!(area.volume(), 4);
+        voxels.fill_box(area, Some(7));
+
+        assert_eq!(voxels[Point3::new(2, 1, 1)], Some(7));
+        assert_eq!(voxels[Point3::new(0, 0, 1)], None);
+        assert_eq!(voxels.get(Point3::new(3, 0, 0)), None);
+        assert_eq!(
+            voxels.layer(1),
+            &[None, Some(7), Some(7), None, Some(7), Some(7)]
+        );
+        assert_eq!(
+            voxels.iter_box(voxels.bounds().unwrap()).flatten().count(),
+            4
+        );
+    }
+}
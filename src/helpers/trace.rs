@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+use super::color::Color;
+use super::print_helpers::Canvas;
+
+/// Something a simulation can hand its state to after every step.
+///
+/// The frame is only built if the tracer wants it, so passing `&mut ()`
+/// costs nothing.
+pub trait Tracer {
+    fn record<F: FnOnce() -> Canvas>(&mut self, frame: F);
+}
+
+impl Tracer for () {
+    fn record<F: FnOnce() -> Canvas>(&mut self, _frame: F) {}
+}
+
+/// Every frame of a simulation, to play back in the terminal or save as an
+/// animated GIF.
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    frames: Vec<Canvas>,
+}
+
+impl Tracer for Trace {
+    fn record<F: FnOnce() -> Canvas>(&mut self, frame: F) {
+        self.frames.push(frame());
+    }
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Trace { frames: Vec::new() }
+    }
+
+    pub fn frames(&self) -> &[Canvas] {
+        &self.frames
+    }
+
+    /// Prints every frame over the last one, waiting `delay` in between.
+    pub fn play(&self, delay: Duration) {
+        for frame in self.frames.iter() {
+            // clear the screen and go back to the top
+            print!("\x1b[2J\x1b[H");
+            frame.print();
+            sleep(delay);
+        }
+    }
+
+    /// The frames as a looping GIF, `delay` is in hundredths of a second.
+    ///
+    /// Frames can be different sizes, the image is as big as the biggest.
+    /// Pixels aren't compressed, which keeps the encoder small.
+    pub fn to_gif(&self, scale: usize, delay: u16) -> Vec<u8> {
+        let frames: Vec<(usize, usize, Vec<Color>)> = self
+            .frames
+            .iter()
+            .map(|frame| {
+                let (width, height) = (frame.cells().cols() * scale, frame.cells().rows() * scale);
+                (width, height, frame.pixels(scale))
+            })
+            .collect();
+        let width = frames.iter().map(|f| f.0).max().unwrap_or(1).max(1);
+        let height = frames.iter().map(|f| f.1).max().unwrap_or(1).max(1);
+
+        let palette = Palette::new(frames.iter().flat_map(|f| f.2.iter().copied()));
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend((width as u16).to_le_bytes());
+        gif.extend((height as u16).to_le_bytes());
+        // global colour table of 256 colours, background colour 0
+        gif.extend([0xf7, 0, 0]);
+        for k in 0..256 {
+            match palette.colors.get(k) {
+                Some(&Color { r, g, b }) => gif.extend([r, g, b]),
+                None => gif.extend([0, 0, 0]),
+            }
+        }
+        // loop forever
+        gif.extend([0x21, 0xff, 0x0b]);
+        gif.extend(b"NETSCAPE2.0");
+        gif.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+        for (frame_width, frame_height, pixels) in frames {
+            gif.extend([0x21, 0xf9, 0x04, 0x00]);
+            gif.extend(delay.to_le_bytes());
+            gif.extend([0x00, 0x00]);
+
+            gif.push(0x2c);
+            gif.extend([0, 0, 0, 0]);
+            gif.extend((frame_width as u16).to_le_bytes());
+            gif.extend((frame_height as u16).to_le_bytes());
+            gif.push(0);
+
+            let indices: Vec<u8> = pixels.iter().map(|&c| palette.index(c)).collect();
+            gif.push(8);
+            for block in lzw_uncompressed(&indices).chunks(255) {
+                gif.push(block.len() as u8);
+                gif.extend(block);
+            }
+            gif.push(0);
+        }
+
+        gif.push(0x3b);
+        gif
+    }
+
+    pub fn write_gif<P: AsRef<Path>>(&self, path: P, scale: usize, delay: u16) -> io::Result<()> {
+        std::fs::write(path, self.to_gif(scale, delay))
+    }
+}
+
+struct Palette {
+    colors: Vec<Color>,
+    indices: HashMap<(u8, u8, u8), u8>,
+    // too many colours, so every colour is rounded to a 6x6x6 cube
+    quantized: bool,
+}
+
+impl Palette {
+    fn new<I: Iterator<Item = Color>>(pixels: I) -> Self {
+        let mut palette = Palette {
+            colors: Vec::new(),
+            indices: HashMap::new(),
+            quantized: false,
+        };
+        for Color { r, g, b } in pixels {
+            if palette.indices.contains_key(&(r, g, b)) {
+                continue;
+            }
+            if palette.colors.len() == 256 {
+                palette.quantized = true;
+                break;
+            }
+            palette
+                .indices
+                .insert((r, g, b), palette.colors.len() as u8);
+            palette.colors.push(Color { r, g, b });
+        }
+
+        if palette.quantized {
+            let level = |k: usize| (k * 51) as u8;
+            palette.colors = (0..216)
+                .map(|k| Color {
+                    r: level(k / 36),
+                    g: level(k / 6 % 6),
+                    b: level(k % 6),
+                })
+                .collect();
+        }
+        palette
+    }
+
+    fn index(&self, Color { r, g, b }: Color) -> u8 {
+        if self.quantized {
+            let round = |v: u8| (v as usize + 25) / 51;
+            (round(r) * 36 + round(g) * 6 + round(b)) as u8
+        } else {
+            self.indices[&(r, g, b)]
+        }
+    }
+}
+
+// LZW codes for 8 bit pixels that only ever uses single pixel codes. The
+// table is cleared before it would need 10 bit codes, so every code is 9
+// bits.
+fn lzw_uncompressed(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    const CODES_PER_CLEAR: usize = 250;
+
+    let mut codes = Vec::with_capacity(indices.len() + indices.len() / CODES_PER_CLEAR + 2);
+    for chunk in indices.chunks(CODES_PER_CLEAR) {
+        codes.push(CLEAR);
+        codes.extend(chunk.iter().map(|&i| i as u16));
+    }
+    codes.push(END);
+
+    // pack the codes least significant bit first
+    let mut bytes = Vec::with_capacity(codes.len() * 9 / 8 + 1);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for code in codes {
+        buffer |= (code as u32) << bits;
+        bits += 9;
+        while bits >= 8 {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            bits -= 8;
+        }
+    }
+    if bits > 0 {
+        bytes.push(buffer as u8);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grid::Grid;
+
+    #[test]
+    fn only_builds_frames_when_tracing() {
+        let grid = Grid::from_vec(vec![true, false], 2);
+        let mut built = 0;
+
+        let mut nobody = ();
+        nobody.record(|| {
+            built += 1;
+            Canvas::from_grid(&grid)
+        });
+        assert_eq!(built, 0);
+
+        let mut trace = Trace::new();
+        trace.record(|| Canvas::from_grid(&grid));
+        trace.record(|| Canvas::from_grid(&grid));
+        assert_eq!(trace.frames().len(), 2);
+    }
+
+    #[test]
+    fn encodes_gifs() {
+        let mut trace = Trace::new();
+        for k in 0..3 {
+            let grid = Grid::from_vec((0..6).map(|i| i == k).collect(), 3);
+            trace.record(|| Canvas::from_grid(&grid));
+        }
+        let gif = trace.to_gif(2, 10);
+
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(&gif[6..10], &[6, 0, 4, 0]);
+        assert_eq!(gif.last(), Some(&0x3b));
+    }
+
+    #[test]
+    fn packs_nine_bit_codes() {
+        // clear (256), 1, end (257)
+        assert_eq!(lzw_uncompressed(&[1]), vec![0x00, 0x03, 0x04, 0x04]);
+    }
+}
@@ -31,6 +31,15 @@ mod helpers;
 mod inputs;
 
 fn main() {
+    // `cargo run -- report 8` prints a day's report instead of the timings
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, mode, day] = &args[..] {
+        if mode == "report" {
+            report(day.parse().expect("a day number"));
+            return;
+        }
+    }
+
     println!("Hello Advent");
     let start = Instant::now();
 
@@ -88,6 +97,15 @@ fn main() {
     println!("Total Time : {:?}", end.duration_since(start));
 }
 
+fn report(day: u8) {
+    let input = get_file(day, InputType::Input);
+    match day {
+        1 => day01::report(&input),
+        8 => day08::report(&input),
+        _ => println!("No report for day {day}"),
+    }
+}
+
 fn time<T, F: Fn(&str) -> Option<T>>(day: u8, part: u8, f: F)
 where
     T: Display + Default,